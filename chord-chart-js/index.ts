@@ -1,7 +1,10 @@
 import initWasm, {
+  chartSections as chartSectionsWasm,
   initSync as initWasmSync,
-  transposeChart,
-  validateChart,
+  movableDoChart as movableDoChartWasm,
+  transposeChart as transposeChartWasm,
+  validateChart as validateChartWasm,
+  validateChartWithDiagnostics as validateChartWithDiagnosticsWasm,
 } from "./pkg/chord_chart_js";
 import wasm from "./pkg/chord_chart_js_bg.wasm";
 
@@ -9,19 +12,170 @@ type ErrorType =
   | "NoNatural"
   | "InvalidNatural"
   | "InvalidNote"
+  | "InvalidMode"
+  | "NoDegree"
+  | "InvalidDegree"
+  | "InvalidOption"
+  | "InvalidQuality"
   | "BarLineShouldStartWithStripe"
-  | "BarLineShouldEndWithStripe";
+  | "BarLineShouldEndWithStripe"
+  | "InvalidRepeatCount"
+  | "RepeatStartWithoutEnd"
+  | "RepeatEndWithoutStart"
+  | "VoltaOutsideRepeat"
+  | "DalSegnoWithoutSegno"
+  | "JumpWithoutCoda"
+  | "AlCodaWithoutToCoda"
+  | "AlFineWithoutFine"
+  | "InvalidTimeSignature";
+type WarningType = "IgnoredAfterNote" | "EmptyBassNote" | "EmptyLine";
+/** Where the error is: line and columns in characters, counting from zero. */
+export interface Span {
+  line: number;
+  start: number;
+  end: number;
+  token: string;
+}
+
 export class ValidationError extends Error {
   type: ErrorType;
   value?: string;
+  span?: Span;
+  /** Stable identifier of the error, like "E002". */
+  code?: string;
+
+  constructor(
+    type_: ErrorType,
+    value?: string,
+    line?: number,
+    start?: number,
+    end?: number,
+    token?: string,
+    code?: string,
+    message?: string
+  ) {
+    super(message);
+    this.type = type_;
+    this.value = value;
+    this.code = code;
+    if (line !== undefined) {
+      this.span = {
+        line,
+        start: start ?? 0,
+        end: end ?? 0,
+        token: token ?? "",
+      };
+    }
+  }
+}
+
+export class Diagnostic {
+  severity: "error" | "warning";
+  type: ErrorType | WarningType;
+  value?: string;
+  span: Span;
+  /** Stable identifier, like "E002" or "W001". */
+  code: string;
+  message: string;
 
-  constructor(type_: ErrorType, value?: string) {
-    super();
+  constructor(
+    severity: "error" | "warning",
+    type_: ErrorType | WarningType,
+    value: string | undefined,
+    line: number,
+    start: number,
+    end: number,
+    token: string,
+    code: string,
+    message: string
+  ) {
+    this.severity = severity;
     this.type = type_;
     this.value = value;
+    this.span = { line, start, end, token };
+    this.code = code;
+    this.message = message;
   }
 }
 
+/** Part of a song: bar lines after a label like "[Chorus]" or "Chorus:". */
+export class Section {
+  /** "Chorus" for both "[Chorus]" and "Chorus:", none before the first label. */
+  label?: string;
+  chart: string;
+
+  constructor(label: string | undefined, chart: string) {
+    this.label = label;
+    this.chart = chart;
+  }
+}
+
+export interface ChartOptions {
+  /** Note names in the chart: "german" reads "B" as Bb and "Fis" as F#, "dutch" reads "Bes" as Bb. */
+  dialect?: "english" | "german" | "scandinavian" | "dutch" | "solfege";
+  /** Rejects chord symbols that are not a known quality, like "Cabc" or "Gmajj7". */
+  strict?: boolean;
+  /** B natural is "H" with "german" (default), "B" with "english" and "Si" with "solfege". */
+  noteNaming?: "english" | "german" | "solfege";
+  /** "ascii" (default) writes "b" and "#", "unicode" writes "♭" and "♯". */
+  accidentals?: "ascii" | "unicode";
+  /** "normal" (default) is "| C | D |", "compact" is "|C|D|". */
+  spacing?: "normal" | "compact";
+  /** Language of error messages: "en" (default), "ru" or "de". */
+  locale?: "en" | "ru" | "de";
+}
+
+export function validateChart(
+  chart: string,
+  options: ChartOptions = {}
+): string {
+  return validateChartWasm(chart, options);
+}
+
+/** Parses what it can and returns the chart with every problem found in it. */
+export function validateChartWithDiagnostics(
+  chart: string,
+  options: ChartOptions = {}
+): { chart: string; diagnostics: Diagnostic[] } {
+  const result = validateChartWithDiagnosticsWasm(chart, options);
+  const value = {
+    chart: result.chart,
+    diagnostics: result.diagnostics as Diagnostic[],
+  };
+  result.free();
+  return value;
+}
+
+/** Splits the chart into labeled song parts. */
+export function chartSections(
+  chart: string,
+  options: ChartOptions = {}
+): Section[] {
+  return chartSectionsWasm(chart, options) as Section[];
+}
+
+export interface TransposeOptions extends ChartOptions {
+  /** Key the chart is in, detected from its chords when left out. */
+  currentKey?: string;
+}
+
+export function transposeChart(
+  chart: string,
+  newKey: string,
+  options: TransposeOptions = {}
+): string {
+  return transposeChartWasm(chart, newKey, options.currentKey, options);
+}
+
+/** Chords as movable-do syllables of the key: "| C G |" in C is "| Do Sol |". */
+export function movableDoChart(
+  chart: string,
+  key: string,
+  options: Pick<ChartOptions, "dialect" | "strict" | "spacing" | "locale"> = {}
+): string {
+  return movableDoChartWasm(chart, key, options);
+}
+
 export default async function init(): Promise<void> {
   const isNode =
     typeof process !== "undefined" &&
@@ -41,5 +195,3 @@ export default async function init(): Promise<void> {
     await initWasm(wasm as unknown as string);
   }
 }
-
-export { validateChart, transposeChart };
@@ -1,6 +1,5 @@
 use std::str::FromStr;
 
-use chord_chart::Transpose;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(module = "@index")]
@@ -9,46 +8,273 @@ extern "C" {
     type ValidationError;
 
     #[wasm_bindgen(constructor)]
-    fn new(type_: &str, value: Option<&str>) -> ValidationError;
+    fn new(
+        type_: &str,
+        value: Option<&str>,
+        line: usize,
+        start: usize,
+        end: usize,
+        token: &str,
+        code: &str,
+        message: &str,
+    ) -> ValidationError;
+
+    #[wasm_bindgen]
+    type Diagnostic;
+
+    #[wasm_bindgen(constructor, js_class = "Diagnostic")]
+    fn new_diagnostic(
+        severity: &str,
+        type_: &str,
+        value: Option<&str>,
+        line: usize,
+        start: usize,
+        end: usize,
+        token: &str,
+        code: &str,
+        message: &str,
+    ) -> Diagnostic;
+
+    #[wasm_bindgen]
+    type Section;
+
+    #[wasm_bindgen(constructor, js_class = "Section")]
+    fn new_section(label: Option<&str>, chart: &str) -> Section;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// `ChartOptions` of index.ts, with fields left out as `None`.
+    pub type ChartOptions;
+
+    #[wasm_bindgen(method, getter)]
+    fn dialect(this: &ChartOptions) -> Option<String>;
+
+    #[wasm_bindgen(method, getter)]
+    fn strict(this: &ChartOptions) -> Option<bool>;
+
+    #[wasm_bindgen(method, getter, js_name = noteNaming)]
+    fn note_naming(this: &ChartOptions) -> Option<String>;
+
+    #[wasm_bindgen(method, getter)]
+    fn accidentals(this: &ChartOptions) -> Option<String>;
+
+    #[wasm_bindgen(method, getter)]
+    fn spacing(this: &ChartOptions) -> Option<String>;
+
+    #[wasm_bindgen(method, getter)]
+    fn locale(this: &ChartOptions) -> Option<String>;
+}
+
+/// Name of the error type in TypeScript and its value.
+fn error_type(error: &chord_chart::Error) -> (&'static str, Option<String>) {
+    use chord_chart::ErrorKind::*;
+    match error.kind() {
+        NoNatural => ("NoNatural", None),
+        InvalidNatural(natural) => ("InvalidNatural", Some(natural.to_string())),
+        InvalidNote(note) => ("InvalidNote", Some(note.to_string())),
+        InvalidMode(mode) => ("InvalidMode", Some(mode.clone())),
+        NoDegree => ("NoDegree", None),
+        InvalidDegree(degree) => ("InvalidDegree", Some(degree.to_string())),
+        InvalidOption(option) => ("InvalidOption", Some(option.clone())),
+        InvalidQuality(quality) => ("InvalidQuality", Some(quality.clone())),
+        BarLineShouldStartWithStripe(line) => ("BarLineShouldStartWithStripe", Some(line.clone())),
+        BarLineShouldEndWithStripe(line) => ("BarLineShouldEndWithStripe", Some(line.clone())),
+        InvalidRepeatCount(count) => ("InvalidRepeatCount", Some(count.clone())),
+        RepeatStartWithoutEnd => ("RepeatStartWithoutEnd", None),
+        RepeatEndWithoutStart => ("RepeatEndWithoutStart", None),
+        VoltaOutsideRepeat => ("VoltaOutsideRepeat", None),
+        DalSegnoWithoutSegno => ("DalSegnoWithoutSegno", None),
+        JumpWithoutCoda => ("JumpWithoutCoda", None),
+        AlCodaWithoutToCoda => ("AlCodaWithoutToCoda", None),
+        AlFineWithoutFine => ("AlFineWithoutFine", None),
+        InvalidTimeSignature(time) => ("InvalidTimeSignature", Some(time.clone())),
+    }
+}
+
+fn warning_type(warning: &chord_chart::Warning) -> (&'static str, Option<String>) {
+    use chord_chart::WarningKind::*;
+    match warning.kind() {
+        IgnoredAfterNote(rest) => ("IgnoredAfterNote", Some(rest.clone())),
+        EmptyBassNote => ("EmptyBassNote", None),
+        EmptyLine => ("EmptyLine", None),
+    }
+}
+
+fn validation_error(error: &chord_chart::Error, locale: chord_chart::Locale) -> ValidationError {
+    let (type_, value) = error_type(error);
+    let span = error.span();
+    ValidationError::new(
+        type_,
+        value.as_deref(),
+        span.line(),
+        span.chars().start,
+        span.chars().end,
+        span.token(),
+        error.kind().code(),
+        &error.kind().message(locale),
+    )
 }
 
 impl From<chord_chart::Error> for ValidationError {
     fn from(error: chord_chart::Error) -> Self {
-        use chord_chart::Error::*;
-        match &error {
-            NoNatural => Self::new("NoNatural", None),
-            InvalidNatural(natural) => Self::new("InvalidNatural", Some(&natural.to_string())),
-            InvalidNote(note) => Self::new("InvalidNote", Some(note)),
-            BarLineShouldStartWithStripe(line) => {
-                Self::new("BarLineShouldStartWithStripe", Some(line))
+        validation_error(&error, chord_chart::Locale::default())
+    }
+}
+
+fn diagnostic(diagnostic: &chord_chart::Diagnostic, locale: chord_chart::Locale) -> Diagnostic {
+    let (severity, (type_, value)) = match diagnostic {
+        chord_chart::Diagnostic::Error(error) => ("error", error_type(error)),
+        chord_chart::Diagnostic::Warning(warning) => ("warning", warning_type(warning)),
+    };
+    let span = diagnostic.span();
+    Diagnostic::new_diagnostic(
+        severity,
+        type_,
+        value.as_deref(),
+        span.line(),
+        span.chars().start,
+        span.chars().end,
+        span.token(),
+        diagnostic.code(),
+        &diagnostic.message(locale),
+    )
+}
+
+/// Options read from `ChartOptions`, with the defaults of the library for the rest.
+struct Options {
+    parse: chord_chart::ParseOptions,
+    render: chord_chart::RenderOptions,
+    locale: chord_chart::Locale,
+}
+
+impl Options {
+    fn read(options: &ChartOptions) -> Result<Self, JsValue> {
+        let locale = match options.locale() {
+            Some(locale) => {
+                chord_chart::Locale::from_str(&locale).map_err(ValidationError::from)?
             }
-            BarLineShouldEndWithStripe(line) => Self::new("BarLineShouldEndWithStripe", Some(line)),
+            None => chord_chart::Locale::default(),
+        };
+        let error = |error| JsValue::from(validation_error(&error, locale));
+        let mut parse = chord_chart::ParseOptions::default();
+        if let Some(dialect) = options.dialect() {
+            parse.dialect = chord_chart::Dialect::from_str(&dialect).map_err(error)?;
+        }
+        parse.strict = options.strict().unwrap_or_default();
+        let mut render = chord_chart::RenderOptions::default();
+        if let Some(note_naming) = options.note_naming() {
+            render.note_naming = chord_chart::NoteNaming::from_str(&note_naming).map_err(error)?;
         }
+        if let Some(accidentals) = options.accidentals() {
+            render.accidentals = chord_chart::Accidentals::from_str(&accidentals).map_err(error)?;
+        }
+        if let Some(spacing) = options.spacing() {
+            render.spacing = chord_chart::Spacing::from_str(&spacing).map_err(error)?;
+        }
+        Ok(Self {
+            parse,
+            render,
+            locale,
+        })
+    }
+
+    fn localized(&self) -> impl Fn(chord_chart::Error) -> JsValue {
+        let locale = self.locale;
+        move |error| JsValue::from(validation_error(&error, locale))
     }
 }
 
 #[wasm_bindgen(js_name = validateChart)]
-pub fn validate_chart(chart: &str) -> Result<String, JsValue> {
-    fn _validate(chart: &str) -> Result<String, ValidationError> {
-        Ok(chord_chart::Chart::from_str(chart)?.to_string())
+pub fn validate_chart(chart: &str, options: &ChartOptions) -> Result<String, JsValue> {
+    let options = Options::read(options)?;
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(options.localized())?;
+    Ok(chart_value.render(&options.render))
+}
+
+/// Partially parsed chart with every problem found in it.
+#[wasm_bindgen]
+pub struct ChartDiagnostics {
+    chart: String,
+    diagnostics: Vec<JsValue>,
+}
+
+#[wasm_bindgen]
+impl ChartDiagnostics {
+    #[wasm_bindgen(getter)]
+    pub fn chart(&self) -> String {
+        self.chart.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> Vec<JsValue> {
+        self.diagnostics.clone()
     }
-    Ok(_validate(chart)?)
 }
+
+#[wasm_bindgen(js_name = validateChartWithDiagnostics)]
+pub fn validate_chart_with_diagnostics(
+    chart: &str,
+    options: &ChartOptions,
+) -> Result<ChartDiagnostics, JsValue> {
+    let options = Options::read(options)?;
+    let (chart_value, diagnostics) = chord_chart::Chart::from_str_recovering(chart, &options.parse);
+    Ok(ChartDiagnostics {
+        chart: chart_value.render(&options.render),
+        diagnostics: diagnostics
+            .iter()
+            .map(|d| diagnostic(d, options.locale).into())
+            .collect(),
+    })
+}
+
+/// Labels of song parts with bar lines in them.
+#[wasm_bindgen(js_name = chartSections)]
+pub fn chart_sections(chart: &str, options: &ChartOptions) -> Result<Vec<JsValue>, JsValue> {
+    let options = Options::read(options)?;
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(options.localized())?;
+    Ok(chart_value
+        .sections()
+        .iter()
+        .map(|s| Section::new_section(s.label(), &s.render(&options.render)).into())
+        .collect())
+}
+
 #[wasm_bindgen(js_name = transposeChart)]
-pub fn transpose_chart(chart: &str, current_key: &str, new_key: &str) -> Result<String, JsValue> {
-    fn _transpose(
-        chart: &str,
-        current_key: &str,
-        new_key: &str,
-    ) -> Result<String, ValidationError> {
-        let current_key_value = chord_chart::Note::from_str(current_key)?;
-        let new_key_value = chord_chart::Note::from_str(new_key)?;
-        Ok(chord_chart::Chart::from_str(chart)?
-            .transpose(
-                &current_key_value.get_semitones_diff(&new_key_value),
-                &new_key_value.accidental().scale(),
-            )
-            .to_string())
-    }
-    Ok(_transpose(chart, current_key, new_key)?)
+pub fn transpose_chart(
+    chart: &str,
+    new_key: &str,
+    current_key: Option<String>,
+    options: &ChartOptions,
+) -> Result<String, JsValue> {
+    let options = Options::read(options)?;
+    let localized = options.localized();
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(&localized)?;
+    let new_key_value =
+        chord_chart::Key::from_str_with(new_key, &options.parse).map_err(&localized)?;
+    let current_key_value = match current_key {
+        Some(key) => chord_chart::Key::from_str_with(&key, &options.parse).map_err(&localized)?,
+        // There's nothing to transpose in a chart without chords
+        None => chart_value
+            .detect_key()
+            .first()
+            .map_or_else(|| new_key_value.clone(), |c| c.key().clone()),
+    };
+    Ok(chart_value
+        .transpose_to(&current_key_value, &new_key_value)
+        .render(&options.render))
+}
+
+/// Chords as movable-do syllables of the key.
+#[wasm_bindgen(js_name = movableDoChart)]
+pub fn movable_do_chart(chart: &str, key: &str, options: &ChartOptions) -> Result<String, JsValue> {
+    let options = Options::read(options)?;
+    let localized = options.localized();
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(&localized)?;
+    let key_value = chord_chart::Key::from_str_with(key, &options.parse).map_err(&localized)?;
+    Ok(chart_value.to_movable_do(&key_value, options.render.spacing))
 }
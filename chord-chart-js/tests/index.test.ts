@@ -1,5 +1,12 @@
 import { beforeAll, describe, expect, it } from "vitest";
-import init, { transposeChart, validateChart, ValidationError } from "../";
+import init, {
+  chartSections,
+  movableDoChart,
+  transposeChart,
+  validateChart,
+  validateChartWithDiagnostics,
+  ValidationError,
+} from "../";
 
 beforeAll(async () => {
   await init();
@@ -10,21 +17,213 @@ describe("validateChart", () => {
     expect(validateChart("| C|")).toEqual("| C |");
   });
 
+  it("renders with options", () => {
+    expect(
+      validateChart("| Bm | F# |", {
+        noteNaming: "english",
+        accidentals: "unicode",
+        spacing: "compact",
+      })
+    ).toEqual("|Bm|F♯|");
+  });
+
+  it("reads dialect", () => {
+    expect(validateChart("| B | Fism | Es |", { dialect: "german" })).toEqual(
+      "| Bb | F#m | Eb |"
+    );
+  });
+
+  it("rejects unknown symbols in strict mode", () => {
+    expect(validateChart("| Gmajj7 |")).toEqual("| Gmajj7 |");
+    expect(() => validateChart("| Gmajj7 |", { strict: true })).toThrowError(
+      "invalid quality: majj7"
+    );
+  });
+
+  it("reads and renders solfege", () => {
+    const chart = "| Do Lam | Sol#m7/Si |";
+    expect(validateChart(chart, { dialect: "solfege" })).toEqual(
+      "| C Am | G#m7/H |"
+    );
+    expect(
+      validateChart(chart, { dialect: "solfege", noteNaming: "solfege" })
+    ).toEqual(chart);
+  });
+
+  it("throws with position", () => {
+    try {
+      validateChart("| C |\n| Am | W7 |");
+      expect.unreachable();
+    } catch (error) {
+      expect((error as ValidationError).span).toEqual({
+        line: 1,
+        start: 7,
+        end: 8,
+        token: "W",
+      });
+      expect((error as ValidationError).code).toEqual("E002");
+    }
+  });
+
+  it("throws in locale", () => {
+    expect(() => validateChart("| W |", { locale: "ru" })).toThrowError(
+      "неверное название ноты: W"
+    );
+    expect(() =>
+      validateChart("| C |", { spacing: "wide" as "compact", locale: "de" })
+    ).toThrowError("ungültige Option: wide");
+  });
+
+  it("throws on invalid option", () => {
+    expect(() =>
+      validateChart("| C |", { spacing: "wide" as "compact" })
+    ).toThrowError("invalid option: wide");
+  });
+
+  it("reads repeats", () => {
+    expect(validateChart("|:C|D:|\n|: Am :| x3")).toEqual(
+      "|: C | D :|\n|: Am :| x3"
+    );
+    expect(() => validateChart("| C |\n|: D |")).toThrow(
+      "repeat start without end"
+    );
+  });
+
+  it("reads voltas", () => {
+    expect(validateChart("|: C |1. D :|\n|2. Em |")).toEqual(
+      "|: C |1. D :|\n|2. Em |"
+    );
+    expect(() => validateChart("| C |2. D |")).toThrow("volta outside repeat");
+  });
+
+  it("reads navigation markers", () => {
+    expect(validateChart("| segno C | G d.s. al fine |\n| Am Fine |")).toEqual(
+      "| Segno C | G D.S. al Fine |\n| Am Fine |"
+    );
+    expect(() => validateChart("| C | G D.S. |")).toThrow("D.S. without segno");
+  });
+
+  it("keeps time signatures", () => {
+    expect(validateChart("4/4\n| C | 3/4 G |\n6/8|Am|")).toEqual(
+      "4/4\n| C | 3/4 G |\n6/8 | Am |"
+    );
+    expect(() => validateChart("3/5 | C |")).toThrow(
+      "invalid time signature: 3/5"
+    );
+  });
+
+  it("keeps section labels", () => {
+    expect(validateChart("[Verse]\n| Am|\nChorus :\n|F |")).toEqual(
+      "[Verse]\n| Am |\nChorus:\n| F |"
+    );
+  });
+
   it("throws", () => {
     expect(() => validateChart("C")).toThrowError(
-      new ValidationError("BarLineShouldStartWithStripe", "C")
+      "bar line should start with stripe: C"
+    );
+  });
+});
+
+describe("chartSections", () => {
+  it("works", () => {
+    expect(chartSections("| C |\n[Chorus]\n| F | G |")).toEqual([
+      { label: undefined, chart: "| C |" },
+      { label: "Chorus", chart: "| F | G |" },
+    ]);
+  });
+
+  it("throws", () => {
+    expect(() => chartSections("[Verse]\nC")).toThrow(
+      "bar line should start with stripe: C"
+    );
+  });
+});
+
+describe("validateChartWithDiagnostics", () => {
+  it("collects all errors", () => {
+    const { chart, diagnostics } = validateChartWithDiagnostics(
+      "| C | W |\n| Am | X7 |\nF |"
+    );
+    expect(chart).toEqual("| C |  |\n| Am |  |\n| F |");
+    expect(diagnostics.map((d) => [d.severity, d.type, d.span.line])).toEqual([
+      ["error", "InvalidNatural", 0],
+      ["error", "InvalidNatural", 1],
+      ["error", "BarLineShouldStartWithStripe", 2],
+    ]);
+  });
+
+  it("reports warnings", () => {
+    const { chart, diagnostics } = validateChartWithDiagnostics(
+      "| C/ | Dm/Fm |"
     );
+    expect(chart).toEqual("| C | Dm/F |");
+    expect(diagnostics.map((d) => [d.severity, d.type, d.value])).toEqual([
+      ["warning", "EmptyBassNote", undefined],
+      ["warning", "IgnoredAfterNote", "m"],
+    ]);
+    expect(diagnostics.map((d) => [d.code, d.message])).toEqual([
+      ["W002", "empty bass note"],
+      ["W001", "ignored after note: m"],
+    ]);
   });
 });
 
 describe("transposeChart", () => {
   it("works", () => {
-    expect(transposeChart("| D |", "D", "Gb")).toEqual("| Gb |");
+    expect(transposeChart("| D |", "Gb", { currentKey: "D" })).toEqual(
+      "| Gb |"
+    );
+  });
+
+  it("detects current key", () => {
+    expect(transposeChart("| Em | Am | H7 | Em |", "Gm")).toEqual(
+      "| Gm | Cm | D7 | Gm |"
+    );
+  });
+
+  it("works with minor keys", () => {
+    expect(transposeChart("| Am | E7 |", "F#m", { currentKey: "Am" })).toEqual(
+      "| F#m | C#7 |"
+    );
+  });
+
+  it("keeps section labels", () => {
+    expect(transposeChart("Intro:\n| D |", "E", { currentKey: "D" })).toEqual(
+      "Intro:\n| E |"
+    );
+  });
+
+  it("renders with options", () => {
+    expect(
+      transposeChart("| A |", "B", { currentKey: "A", noteNaming: "english" })
+    ).toEqual("| B |");
   });
 
   it("throws", () => {
-    expect(() => transposeChart("C", "C", "C")).toThrow(
-      new ValidationError("BarLineShouldStartWithStripe", "C")
+    expect(() => transposeChart("C", "C", { currentKey: "C" })).toThrow(
+      "bar line should start with stripe: C"
+    );
+  });
+});
+
+describe("movableDoChart", () => {
+  it("works", () => {
+    expect(movableDoChart("| Am | Dm | E7 |", "Am")).toEqual(
+      "| Lam | Rem | Mi7 |"
     );
   });
+
+  it("renders with options", () => {
+    expect(
+      movableDoChart("| Fis | Cis7 |", "Fis", {
+        dialect: "german",
+        spacing: "compact",
+      })
+    ).toEqual("|Do|Sol7|");
+  });
+
+  it("throws", () => {
+    expect(() => movableDoChart("| C |", "X")).toThrow("invalid natural: X");
+  });
 });
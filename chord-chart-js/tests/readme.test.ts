@@ -1,5 +1,11 @@
 import { expect, it } from "vitest";
-import init, { transposeChart, validateChart, ValidationError } from "../";
+import init, {
+  chartSections,
+  movableDoChart,
+  transposeChart,
+  validateChart,
+  validateChartWithDiagnostics,
+} from "../";
 
 it("works", async () => {
   await init();
@@ -12,10 +18,82 @@ it("works", async () => {
 
   // and that's not a valid one: chords without stripes between lines of bars
   expect(() => validateChart("A/E E\nC#m")).toThrow(
-    new ValidationError("BarLineShouldStartWithStripe", "A/E E")
+    "bar line should start with stripe: A/E E"
   );
 
-  expect(transposeChart("| A/E| E |\n| C#m|", "E", "Db")).toEqual(
-    "| Gb/Db | Db |\n| Bbm |"
+  // B natural is written as H by default
+  expect(
+    validateChart("| Bm | F# |", {
+      noteNaming: "english",
+      accidentals: "unicode",
+      spacing: "compact",
+    })
+  ).toEqual("|Bm|F♯|");
+
+  // repeated bars, played four times
+  expect(validateChart("|:C G|Am F:| x4")).toEqual("|: C G | Am F :| x4");
+
+  // first and second endings
+  expect(validateChart("|: C | 1. G :| 2. F |")).toEqual(
+    "|: C |1. G :|2. F |"
+  );
+
+  // navigation markers
+  expect(validateChart("| segno C | Am Fine | G d.s. al fine |")).toEqual(
+    "| Segno C | Am Fine | G D.S. al Fine |"
+  );
+
+  // time signatures for the chart, a line or a bar
+  expect(validateChart("6/8\n| C | 3/4 G |")).toEqual("6/8\n| C | 3/4 G |");
+
+  // all errors at once, with the part of the chart that could be read
+  const { chart, diagnostics } = validateChartWithDiagnostics(
+    "| C | W |\n| Am | X7 |"
+  );
+  expect(chart).toEqual("| C |  |\n| Am |  |");
+  expect(diagnostics.map((d) => d.span)).toEqual([
+    { line: 0, start: 6, end: 7, token: "W" },
+    { line: 1, start: 7, end: 8, token: "X" },
+  ]);
+
+  // German and Dutch charts: B is Bb in German, Fis is F#
+  expect(validateChart("| B | Fism | Es |", { dialect: "german" })).toEqual(
+    "| Bb | F#m | Eb |"
+  );
+
+  // symbols after the root are not checked unless strict
+  expect(() => validateChart("| Gmajj7 |", { strict: true })).toThrowError(
+    "invalid quality: majj7"
+  );
+
+  // messages are in English, Russian or German
+  expect(() => validateChart("| W |", { locale: "ru" })).toThrow(
+    "неверное название ноты: W"
+  );
+
+  // song parts are labeled as "[Verse]" or "Chorus:"
+  expect(
+    chartSections("[Verse]\n| Am | C |\nChorus:\n| F | G |").map(
+      (s) => s.label
+    )
+  ).toEqual(["Verse", "Chorus"]);
+
+  expect(
+    transposeChart("| A/E| E |\n| C#m|", "Db", { currentKey: "E" })
+  ).toEqual("| Gb/Db | Db |\n| Bbm |");
+
+  // keys can be minor or modal: "Eb dorian"
+  expect(transposeChart("| Am | E7 |", "F#m", { currentKey: "Am" })).toEqual(
+    "| F#m | C#7 |"
+  );
+
+  // current key is detected from chords when it's not given
+  expect(transposeChart("| Em | Am | H7 | Em |", "Gm")).toEqual(
+    "| Gm | Cm | D7 | Gm |"
+  );
+
+  // movable do: minor keys are la-based
+  expect(movableDoChart("| Am | Dm | E7 |", "Am")).toEqual(
+    "| Lam | Rem | Mi7 |"
   );
 });
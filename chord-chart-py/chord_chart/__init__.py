@@ -1,3 +1,19 @@
-from chord_chart._chord_chart import ValidationError, transpose_chart, validate_chart
+from chord_chart._chord_chart import (
+    Diagnostic,
+    ValidationError,
+    chart_sections,
+    movable_do_chart,
+    transpose_chart,
+    validate_chart,
+    validate_chart_with_diagnostics,
+)
 
-__all__ = ["ValidationError", "transpose_chart", "validate_chart"]
+__all__ = [
+    "Diagnostic",
+    "ValidationError",
+    "chart_sections",
+    "movable_do_chart",
+    "transpose_chart",
+    "validate_chart",
+    "validate_chart_with_diagnostics",
+]
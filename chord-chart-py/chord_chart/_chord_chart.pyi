@@ -1,4 +1,75 @@
-def validate_chart(chart: str) -> str: ...
-def transpose_chart(chart: str, current_key: str, new_key: str) -> str: ...
+from typing import Literal
 
-class ValidationError(Exception): ...
+Dialect = Literal["english", "german", "scandinavian", "dutch", "solfege"]
+NoteNaming = Literal["english", "german", "solfege"]
+Accidentals = Literal["ascii", "unicode"]
+Spacing = Literal["normal", "compact"]
+Locale = Literal["en", "ru", "de"]
+
+def validate_chart(
+    chart: str,
+    *,
+    dialect: Dialect = "english",
+    strict: bool = False,
+    note_naming: NoteNaming = "german",
+    accidentals: Accidentals = "ascii",
+    spacing: Spacing = "normal",
+    locale: Locale = "en",
+) -> str: ...
+def validate_chart_with_diagnostics(
+    chart: str,
+    *,
+    dialect: Dialect = "english",
+    strict: bool = False,
+    note_naming: NoteNaming = "german",
+    accidentals: Accidentals = "ascii",
+    spacing: Spacing = "normal",
+    locale: Locale = "en",
+) -> tuple[str, list[Diagnostic]]: ...
+def chart_sections(
+    chart: str,
+    *,
+    dialect: Dialect = "english",
+    strict: bool = False,
+    note_naming: NoteNaming = "german",
+    accidentals: Accidentals = "ascii",
+    spacing: Spacing = "normal",
+    locale: Locale = "en",
+) -> list[tuple[str | None, str]]: ...
+def transpose_chart(
+    chart: str,
+    new_key: str,
+    *,
+    current_key: str | None = None,
+    dialect: Dialect = "english",
+    strict: bool = False,
+    note_naming: NoteNaming = "german",
+    accidentals: Accidentals = "ascii",
+    spacing: Spacing = "normal",
+    locale: Locale = "en",
+) -> str: ...
+def movable_do_chart(
+    chart: str,
+    key: str,
+    *,
+    dialect: Dialect = "english",
+    strict: bool = False,
+    spacing: Spacing = "normal",
+    locale: Locale = "en",
+) -> str: ...
+
+class Diagnostic:
+    severity: Literal["error", "warning"]
+    code: str
+    message: str
+    line: int
+    start: int
+    end: int
+    token: str
+
+class ValidationError(Exception):
+    code: str
+    line: int
+    start: int
+    end: int
+    token: str
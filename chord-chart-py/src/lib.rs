@@ -1,59 +1,274 @@
 use std::str::FromStr;
 
-use chord_chart::Transpose;
-use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyTypeError},
+    prelude::*,
+    types::PyDict,
+};
 
 create_exception!(_chord_chart, ValidationError, PyException);
 
+/// Error with the language its message is shown in.
 #[derive(Debug)]
-struct Error(chord_chart::Error);
+struct Error(chord_chart::Error, chord_chart::Locale);
+
+impl Error {
+    fn in_locale(locale: chord_chart::Locale) -> impl Fn(chord_chart::Error) -> Self {
+        move |error| Self(error, locale)
+    }
+}
 
 impl From<chord_chart::Error> for Error {
     fn from(error: chord_chart::Error) -> Self {
-        Self(error)
+        Self(error, chord_chart::Locale::default())
     }
 }
 
 impl From<Error> for PyErr {
     fn from(error: Error) -> Self {
-        use chord_chart::Error::*;
-
-        let str: String = match &error.0 {
-            NoNatural => "no natural".into(),
-            InvalidNatural(natural) => format!("invalid natural: {natural}"),
-            InvalidNote(note) => format!("invalid note: {note}"),
-            BarLineShouldStartWithStripe(line) => {
-                format!("bar line should start with stripe: {line}")
+        // Position in the chart, in characters as Python counts them
+        let span = error.0.span();
+        let err = ValidationError::new_err(error.0.kind().message(error.1));
+        Python::with_gil(|py| {
+            let value = err.value(py);
+            let attrs = [
+                ("code", error.0.kind().code().into_py(py)),
+                ("line", span.line().into_py(py)),
+                ("start", span.chars().start.into_py(py)),
+                ("end", span.chars().end.into_py(py)),
+                ("token", span.token().into_py(py)),
+            ];
+            for (name, attr) in attrs {
+                value
+                    .setattr(name, attr)
+                    .expect("exception should accept attributes");
             }
-            BarLineShouldEndWithStripe(line) => format!("bar line should end with stripe: {line}"),
-        };
+        });
+        err
+    }
+}
 
-        ValidationError::new_err(str)
+/// Problem found in a chart, positioned in characters as Python counts them.
+#[pyclass(frozen)]
+struct Diagnostic {
+    #[pyo3(get)]
+    severity: &'static str,
+    #[pyo3(get)]
+    code: &'static str,
+    #[pyo3(get)]
+    message: String,
+    #[pyo3(get)]
+    line: usize,
+    #[pyo3(get)]
+    start: usize,
+    #[pyo3(get)]
+    end: usize,
+    #[pyo3(get)]
+    token: String,
+}
+
+impl Diagnostic {
+    fn new(diagnostic: &chord_chart::Diagnostic, locale: chord_chart::Locale) -> Self {
+        let span = diagnostic.span();
+        Self {
+            severity: if diagnostic.is_error() {
+                "error"
+            } else {
+                "warning"
+            },
+            code: diagnostic.code(),
+            message: diagnostic.message(locale),
+            line: span.line(),
+            start: span.chars().start,
+            end: span.chars().end,
+            token: span.token().into(),
+        }
     }
 }
 
-#[pyfunction]
-fn validate_chart(chart: &str) -> Result<String, Error> {
-    Ok(chord_chart::Chart::from_str(chart)?.to_string())
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic({}: {} at {}:{}..{})",
+            self.severity, self.message, self.line, self.start, self.end
+        )
+    }
 }
 
-#[pyfunction]
-fn transpose_chart(chart: &str, current_key: &str, new_key: &str) -> Result<String, Error> {
-    let current_key_value = chord_chart::Note::from_str(current_key)?;
-    let new_key_value = chord_chart::Note::from_str(new_key)?;
-    Ok(chord_chart::Chart::from_str(chart)?
-        .transpose(
-            &current_key_value.get_semitones_diff(&new_key_value),
-            &new_key_value.accidental().scale(),
+fn parse_options(
+    dialect: &str,
+    strict: bool,
+) -> Result<chord_chart::ParseOptions, chord_chart::Error> {
+    Ok(chord_chart::ParseOptions {
+        dialect: chord_chart::Dialect::from_str(dialect)?,
+        strict,
+    })
+}
+
+fn render_options(
+    note_naming: &str,
+    accidentals: &str,
+    spacing: &str,
+) -> Result<chord_chart::RenderOptions, chord_chart::Error> {
+    Ok(chord_chart::RenderOptions {
+        note_naming: chord_chart::NoteNaming::from_str(note_naming)?,
+        accidentals: chord_chart::Accidentals::from_str(accidentals)?,
+        spacing: chord_chart::Spacing::from_str(spacing)?,
+    })
+}
+
+/// Keyword options of every function.
+const OPTIONS: [&str; 6] = [
+    "dialect",
+    "strict",
+    "note_naming",
+    "accidentals",
+    "spacing",
+    "locale",
+];
+
+/// Options read from keywords, with the defaults of the library for the rest.
+struct Options {
+    parse: chord_chart::ParseOptions,
+    render: chord_chart::RenderOptions,
+    locale: chord_chart::Locale,
+}
+
+impl Options {
+    /// Reads the keywords in `names`, rejecting others as Python does.
+    fn extract(options: Option<&PyDict>, names: &[&str]) -> PyResult<Self> {
+        for name in options.map_or_else(Vec::new, |o| o.keys().iter().collect()) {
+            let name: &str = name.extract()?;
+            if !names.contains(&name) {
+                let message = format!("got an unexpected keyword argument '{name}'");
+                return Err(PyTypeError::new_err(message));
+            }
+        }
+        let locale =
+            chord_chart::Locale::from_str(option(options, "locale", "en")?).map_err(Error::from)?;
+        let localized = Error::in_locale(locale);
+        let parse = parse_options(
+            option(options, "dialect", "english")?,
+            option(options, "strict", false)?,
         )
-        .to_string())
+        .map_err(&localized)?;
+        let render = render_options(
+            option(options, "note_naming", "german")?,
+            option(options, "accidentals", "ascii")?,
+            option(options, "spacing", "normal")?,
+        )
+        .map_err(&localized)?;
+        Ok(Self {
+            parse,
+            render,
+            locale,
+        })
+    }
+
+    fn localized(&self) -> impl Fn(chord_chart::Error) -> Error {
+        Error::in_locale(self.locale)
+    }
+}
+
+fn option<'a, T: FromPyObject<'a>>(
+    options: Option<&'a PyDict>,
+    name: &str,
+    default: T,
+) -> PyResult<T> {
+    options
+        .and_then(|o| o.get_item(name))
+        .map_or(Ok(default), PyAny::extract)
+}
+
+#[pyfunction(chart, "*", options = "**")]
+fn validate_chart(chart: &str, options: Option<&PyDict>) -> PyResult<String> {
+    let options = Options::extract(options, &OPTIONS)?;
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(options.localized())?;
+    Ok(chart_value.render(&options.render))
+}
+
+#[pyfunction(chart, "*", options = "**")]
+fn validate_chart_with_diagnostics(
+    chart: &str,
+    options: Option<&PyDict>,
+) -> PyResult<(String, Vec<Diagnostic>)> {
+    let options = Options::extract(options, &OPTIONS)?;
+    let (chart_value, diagnostics) = chord_chart::Chart::from_str_recovering(chart, &options.parse);
+    Ok((
+        chart_value.render(&options.render),
+        diagnostics
+            .iter()
+            .map(|d| Diagnostic::new(d, options.locale))
+            .collect(),
+    ))
+}
+
+/// Labels of song parts with bar lines in them, `None` for bar lines before the first label.
+#[pyfunction(chart, "*", options = "**")]
+fn chart_sections(
+    chart: &str,
+    options: Option<&PyDict>,
+) -> PyResult<Vec<(Option<String>, String)>> {
+    let options = Options::extract(options, &OPTIONS)?;
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(options.localized())?;
+    Ok(chart_value
+        .sections()
+        .iter()
+        .map(|s| (s.label().map(String::from), s.render(&options.render)))
+        .collect())
+}
+
+#[pyfunction(chart, new_key, "*", current_key = "None", options = "**")]
+fn transpose_chart(
+    chart: &str,
+    new_key: &str,
+    current_key: Option<&str>,
+    options: Option<&PyDict>,
+) -> PyResult<String> {
+    let options = Options::extract(options, &OPTIONS)?;
+    let localized = options.localized();
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(&localized)?;
+    let new_key_value =
+        chord_chart::Key::from_str_with(new_key, &options.parse).map_err(&localized)?;
+    let current_key_value = match current_key {
+        Some(key) => chord_chart::Key::from_str_with(key, &options.parse).map_err(&localized)?,
+        // There's nothing to transpose in a chart without chords
+        None => chart_value
+            .detect_key()
+            .first()
+            .map_or_else(|| new_key_value.clone(), |c| c.key().clone()),
+    };
+    Ok(chart_value
+        .transpose_to(&current_key_value, &new_key_value)
+        .render(&options.render))
+}
+
+/// Chords as movable-do syllables of the key: "| C G |" in C is "| Do Sol |".
+#[pyfunction(chart, key, "*", options = "**")]
+fn movable_do_chart(chart: &str, key: &str, options: Option<&PyDict>) -> PyResult<String> {
+    let names = ["dialect", "strict", "spacing", "locale"];
+    let options = Options::extract(options, &names)?;
+    let localized = options.localized();
+    let chart_value =
+        chord_chart::Chart::from_str_with(chart, &options.parse).map_err(&localized)?;
+    let key_value = chord_chart::Key::from_str_with(key, &options.parse).map_err(localized)?;
+    Ok(chart_value.to_movable_do(&key_value, options.render.spacing))
 }
 
 #[pymodule]
 #[pyo3(name = "_chord_chart")]
 fn module(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(validate_chart, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_chart_with_diagnostics, m)?)?;
     m.add_function(wrap_pyfunction!(transpose_chart, m)?)?;
+    m.add_function(wrap_pyfunction!(chart_sections, m)?)?;
+    m.add_function(wrap_pyfunction!(movable_do_chart, m)?)?;
+    m.add_class::<Diagnostic>()?;
     m.add("ValidationError", py.get_type::<ValidationError>())?;
     Ok(())
 }
@@ -1,21 +1,225 @@
 import pytest
 
-from chord_chart import ValidationError, transpose_chart, validate_chart
+from chord_chart import (
+    ValidationError,
+    chart_sections,
+    movable_do_chart,
+    transpose_chart,
+    validate_chart,
+    validate_chart_with_diagnostics,
+)
 
 
 def test_validate_chart_works():
     assert validate_chart("| C|") == "| C |"
 
 
+def test_validate_chart_renders_with_options():
+    assert (
+        validate_chart(
+            "| Bm | F# |",
+            note_naming="english",
+            accidentals="unicode",
+            spacing="compact",
+        )
+        == "|Bm|F♯|"
+    )
+
+
+def test_validate_chart_throws_on_invalid_option():
+    with pytest.raises(ValidationError, match="invalid option: dutch"):
+        validate_chart("| C |", note_naming="dutch")  # type: ignore
+
+
+def test_validate_chart_rejects_unknown_option():
+    with pytest.raises(TypeError, match="unexpected keyword argument 'naming'"):
+        validate_chart("| C |", naming="english")  # type: ignore
+    with pytest.raises(TypeError):
+        validate_chart("| C |", strict="yes")  # type: ignore
+
+
+def test_validate_chart_reads_dialect():
+    assert validate_chart("| B | Fism | Es |", dialect="german") == "| Bb | F#m | Eb |"
+
+
+def test_validate_chart_rejects_unknown_symbols_in_strict_mode():
+    assert validate_chart("| Gmajj7 |") == "| Gmajj7 |"
+    with pytest.raises(ValidationError, match="invalid quality: majj7") as exc_info:
+        validate_chart("| Gmajj7 |", strict=True)
+    assert (exc_info.value.start, exc_info.value.token) == (6, "j")
+
+
+def test_validate_chart_reads_and_renders_solfege():
+    chart = "| Do Lam | Sol#m7/Si |"
+    assert validate_chart(chart, dialect="solfege") == "| C Am | G#m7/H |"
+    assert validate_chart(chart, dialect="solfege", note_naming="solfege") == chart
+
+
 def test_validate_chart_throws():
     with pytest.raises(ValidationError, match="bar line should start with stripe: C"):
         validate_chart("C")
 
 
+def test_validate_chart_throws_with_position():
+    with pytest.raises(ValidationError, match="invalid natural: W") as exc_info:
+        validate_chart("| C |\n| Am | W7 |")
+    assert (exc_info.value.line, exc_info.value.start, exc_info.value.end) == (1, 7, 8)
+    assert exc_info.value.token == "W"
+    assert exc_info.value.code == "E002"
+
+
+def test_validate_chart_throws_in_locale():
+    with pytest.raises(ValidationError, match="неверное название ноты: W"):
+        validate_chart("| W |", locale="ru")
+    with pytest.raises(ValidationError, match="ungültige Option: wide"):
+        validate_chart("| C |", spacing="wide", locale="de")
+    with pytest.raises(ValidationError, match="invalid option: fr"):
+        validate_chart("| C |", locale="fr")
+
+
+def test_validate_chart_reads_repeats():
+    assert validate_chart("|:C|D:|\n|: Am :| x3") == "|: C | D :|\n|: Am :| x3"
+    with pytest.raises(ValidationError, match="repeat start without end") as exc_info:
+        validate_chart("| C |\n|: D |")
+    assert (exc_info.value.code, exc_info.value.line, exc_info.value.start) == (
+        "E012",
+        1,
+        0,
+    )
+
+
+def test_validate_chart_reads_voltas():
+    chart = "|: C |1. D :|\n|2. Em |"
+    assert validate_chart(chart) == chart
+    assert (
+        transpose_chart(chart, current_key="C", new_key="D")
+        == "|: D |1. E :|\n|2. F#m |"
+    )
+    with pytest.raises(ValidationError, match="volta outside repeat") as exc_info:
+        validate_chart("| C |2. D |")
+    assert (exc_info.value.code, exc_info.value.token) == ("E014", "2.")
+
+
+def test_validate_chart_reads_navigation_markers():
+    assert (
+        validate_chart("| segno C | G d.s. al fine |\n| Am Fine |")
+        == "| Segno C | G D.S. al Fine |\n| Am Fine |"
+    )
+    with pytest.raises(ValidationError, match="D.S. without segno") as exc_info:
+        validate_chart("| C | G D.S. |")
+    assert (exc_info.value.code, exc_info.value.token) == ("E015", "D.S.")
+
+
+def test_validate_chart_keeps_time_signatures():
+    chart = "4/4\n| C | 3/4 G |\n6/8 | Am |"
+    assert validate_chart(chart) == chart
+    assert (
+        transpose_chart(chart, current_key="C", new_key="D")
+        == "4/4\n| D | 3/4 A |\n6/8 | Hm |"
+    )
+    with pytest.raises(ValidationError, match="invalid time signature") as exc_info:
+        validate_chart("3/5 | C |")
+    assert (exc_info.value.code, exc_info.value.token) == ("E019", "3/5")
+
+
+def test_validate_chart_with_diagnostics_collects_all_errors():
+    chart, diagnostics = validate_chart_with_diagnostics("| C | W |\n| Am | X7 |\nF |")
+    assert chart == "| C |  |\n| Am |  |\n| F |"
+    assert [(d.severity, d.message, d.line, d.start) for d in diagnostics] == [
+        ("error", "invalid natural: W", 0, 6),
+        ("error", "invalid natural: X", 1, 7),
+        ("error", "bar line should start with stripe: F |", 2, 0),
+    ]
+
+
+def test_validate_chart_with_diagnostics_reports_warnings():
+    chart, diagnostics = validate_chart_with_diagnostics("| C/ | Dm/Fm |")
+    assert chart == "| C | Dm/F |"
+    assert [(d.severity, d.message, d.start, d.token) for d in diagnostics] == [
+        ("warning", "empty bass note", 3, "/"),
+        ("warning", "ignored after note: m", 11, "m"),
+    ]
+    assert [d.code for d in diagnostics] == ["W002", "W001"]
+    _, diagnostics = validate_chart_with_diagnostics("| C/ |", locale="de")
+    assert diagnostics[0].message == "Bassnote fehlt"
+
+
+def test_validate_chart_keeps_section_labels():
+    chart = "[Verse]\n| Am | C |\nChorus:\n| F | G |"
+    assert validate_chart(chart) == chart
+    assert transpose_chart(chart, current_key="Am", new_key="Em") == (
+        "[Verse]\n| Em | G |\nChorus:\n| C | D |"
+    )
+
+
+def test_chart_sections_works():
+    assert chart_sections("| C |\n[Verse]\n| Am | C |\n| F |") == [
+        (None, "| C |"),
+        ("Verse", "| Am | C |\n| F |"),
+    ]
+
+
 def test_tranpose_chart_works():
     assert transpose_chart("| D |", current_key="D", new_key="Gb") == "| Gb |"
 
 
+def test_tranpose_chart_works_with_minor_keys():
+    assert (
+        transpose_chart("| Am | E7 |", current_key="Am", new_key="F#m")
+        == "| F#m | C#7 |"
+    )
+
+
+def test_tranpose_chart_detects_current_key():
+    assert (
+        transpose_chart("| Em | Am | H7 | Em |", current_key=None, new_key="Gm")
+        == "| Gm | Cm | D7 | Gm |"
+    )
+
+
+def test_tranpose_chart_without_current_key():
+    assert transpose_chart("| Em | Am | H7 | Em |", "Hm") == "| Hm | Em | F#7 | Hm |"
+    with pytest.raises(TypeError, match="new_key"):
+        transpose_chart("| C |")
+
+
+def test_tranpose_chart_takes_current_key_by_name():
+    assert transpose_chart("| A/E | E |", "Db", current_key="E") == "| Gb/Db | Db |"
+    with pytest.raises(TypeError):
+        transpose_chart("| A/E | E |", "E", "Db")
+
+
+def test_tranpose_chart_renders_with_options():
+    assert (
+        transpose_chart("| A |", "B", current_key="A", note_naming="english") == "| B |"
+    )
+
+
+def test_tranpose_chart_reads_dialect():
+    assert (
+        transpose_chart("| Fis | Cis7 |", "As", current_key="Fis", dialect="german")
+        == "| Ab | Eb7 |"
+    )
+
+
 def test_tranpose_chart_throws():
     with pytest.raises(ValidationError, match="bar line should start with stripe: C"):
-        transpose_chart("C", "C", "C")
+        transpose_chart("C", "C", current_key="C")
+
+
+def test_movable_do_chart_works():
+    assert movable_do_chart("| Am | Dm | E7 |", "Am") == "| Lam | Rem | Mi7 |"
+
+
+def test_movable_do_chart_renders_with_options():
+    assert (
+        movable_do_chart("| Fis | Cis7 |", "Fis", dialect="german", spacing="compact")
+        == "|Do|Sol7|"
+    )
+
+
+def test_movable_do_chart_throws():
+    with pytest.raises(ValidationError, match="invalid natural: X"):
+        movable_do_chart("| C |", "X")
+    with pytest.raises(TypeError, match="note_naming"):
+        movable_do_chart("| C |", "C", note_naming="english")  # type: ignore
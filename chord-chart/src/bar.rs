@@ -1,61 +1,251 @@
 use crate::chord::Chord;
-use crate::error::Error;
+use crate::cst::{TokenKind, Tokens};
+use crate::diagnostic::{extend_within, first_error, Diagnostic};
+use crate::error::{offset_in, Error, ErrorKind};
+use crate::navigation::{Marker, Signs};
+use crate::note::Note;
+use crate::render::RenderOptions;
+use crate::time::TimeSignature;
 use crate::transpose::{Scale, Transpose};
-use std::str::FromStr;
+use crate::volta::Volta;
+use std::{fmt, ops::Range, str::FromStr};
 
+/// Chords of a bar, or what they are analysed as, with signs written around them.
 #[derive(Debug)]
-pub struct Bar(Vec<Chord>);
+pub struct Bar<C = Chord> {
+    chords: Vec<C>,
+    /// Ending that starts at this bar.
+    volta: Option<Volta>,
+    /// Change of meter from this bar on.
+    time: Option<TimeSignature>,
+    markers: Vec<Marker>,
+}
+
+impl<C> Bar<C> {
+    pub fn chords(&self) -> &[C] {
+        &self.chords
+    }
+
+    pub(crate) fn volta(&self) -> Option<&Volta> {
+        self.volta.as_ref()
+    }
+
+    /// Time signature written at the start of the bar.
+    pub fn time_signature(&self) -> Option<TimeSignature> {
+        self.time
+    }
+
+    /// Segno, D.C. and other navigation, in the order they are written.
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    /// Same bar with each chord replaced by `f` of it.
+    pub(crate) fn map<D>(&self, f: &impl Fn(&C) -> D) -> Bar<D> {
+        Bar {
+            chords: self.chords.iter().map(f).collect(),
+            volta: self.volta.clone(),
+            time: self.time,
+            markers: self.markers.clone(),
+        }
+    }
+
+    pub(crate) fn format_with(&self, format_chord: &impl Fn(&C) -> String) -> String {
+        let volta = self.volta.iter().map(Volta::to_string);
+        let time = self.time.iter().map(TimeSignature::to_string);
+        let (start, end): (Vec<&Marker>, Vec<&Marker>) =
+            self.markers.iter().partition(|m| m.is_start());
+        volta
+            .chain(time)
+            .chain(start.iter().map(ToString::to_string))
+            .chain(self.chords.iter().map(format_chord))
+            .chain(end.iter().map(ToString::to_string))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl Bar {
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.format_with(&|c| c.render(options))
+    }
+
+    pub(crate) fn parse_with(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+    ) -> Result<Self, Error> {
+        // Bar alone can't tell if its signs are matched
+        first_error(|diagnostics| {
+            let mut signs = Signs::default();
+            Self::parse_into(
+                s,
+                parse_chord,
+                &mut signs,
+                &mut Tokens::default(),
+                diagnostics,
+            )
+        })
+    }
+
+    /// Parses what it can, leaving out chords that can't be read.
+    /// Ending and markers go to `signs`, to be checked with the rest of the chart,
+    /// and everything read to `tokens`.
+    /// Time signature comes first after the ending, unless it reads as a slash chord:
+    /// "5/4" in Nashville numbers is one, "6/8" is not. Digits that are neither, like "3/5",
+    /// are an invalid time signature.
+    pub(crate) fn parse_into(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+        signs: &mut Signs,
+        tokens: &mut Tokens,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Self {
+        let mut rest = s.trim_start();
+        let volta = Volta::parse_prefix(rest).map(|(volta, after)| {
+            let text = &rest[..rest.len() - after.len()];
+            tokens.push(TokenKind::Volta, offset(s, text));
+            signs.repeat(Error::new(
+                ErrorKind::VoltaOutsideRepeat,
+                s,
+                offset(s, text),
+            ));
+            rest = after;
+            volta
+        });
+        rest = rest.trim_start();
+        let word = &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())];
+        // First word is read once, as a chord unless it's a time signature
+        let mut time = None;
+        let mut first = None;
+        if !word.is_empty() && Marker::parse_prefix(rest).is_none() {
+            let mut found = Vec::new();
+            let chord = parse_chord(word, &mut found);
+            let read = chord.is_ok() && !found.iter().any(Diagnostic::is_error);
+            let slash_chord = read && chord.as_ref().is_ok_and(|c| c.bass_note().is_some());
+            match TimeSignature::from_str(word) {
+                Ok(value) if !slash_chord => time = Some(value),
+                Err(err) if !read && TimeSignature::looks_like(word) => {
+                    diagnostics.push(err.within(s, word).into())
+                }
+                _ => first = Some((chord, found)),
+            }
+            if first.is_none() {
+                tokens.push(TokenKind::TimeSignature, offset(s, word));
+                rest = &rest[word.len()..];
+            }
+        }
+
+        let mut vec = Vec::new();
+        let mut markers = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some((marker, after)) = Marker::parse_prefix(rest) {
+                let bytes = offset(s, &rest[..rest.len() - after.len()]);
+                tokens.push(TokenKind::Marker, bytes.clone());
+                signs.marker(marker, s, bytes);
+                markers.push(marker);
+                rest = after;
+                continue;
+            }
+
+            let chord = &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())];
+            tokens.push(TokenKind::Chord, offset(s, chord));
+            let (parsed, mut found) = first.take().unwrap_or_else(|| {
+                let mut found = Vec::new();
+                (parse_chord(chord, &mut found), found)
+            });
+            match parsed {
+                Ok(value) => vec.push(value),
+                Err(err) => found.push(err.into()),
+            }
+            extend_within(diagnostics, found, s, chord);
+            rest = &rest[chord.len()..];
+        }
+        Bar {
+            chords: vec,
+            volta,
+            time,
+            markers,
+        }
+    }
+}
+
+/// Bytes of `part` in `s`.
+fn offset(s: &str, part: &str) -> Range<usize> {
+    let start = offset_in(s, part);
+    start..start + part.len()
+}
 
 impl FromStr for Bar {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut vec = Vec::new();
-        for s in s.split_whitespace() {
-            vec.push(Chord::from_str(s)?);
-        }
-        Ok(Bar(vec))
+        Self::parse_with(s, &|s, d| Chord::parse_into(s, &Note::parse_prefix, d))
     }
 }
 
-impl ToString for Bar {
-    fn to_string(&self) -> String {
-        self.0
-            .iter()
-            .map(Chord::to_string)
-            .collect::<Vec<String>>()
-            .join(" ")
+impl fmt::Display for Bar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
     }
 }
 
 impl Transpose for Bar {
     fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
-        Bar(self
-            .0
-            .iter()
-            .map(|c| c.transpose(semitone_incr, scale))
-            .collect())
+        self.map(&|c| c.transpose(semitone_incr, scale))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Scale;
     use super::*;
+    use crate::note::Note;
     use test_case::case;
 
     #[case("C Dm/F#", "C Dm/F#")]
     #[case("C dm/f#  D", "C Dm/F# D")]
+    #[case("1. C  D", "1. C D")]
+    #[case("2.Am", "2. Am")]
+    #[case(" 1,2. C", "1,2. C")]
+    #[case("segno C  to coda", "Segno C To Coda")]
+    #[case("C Coda D.S. al Fine", "Coda C D.S. al Fine")]
+    #[case("1. 𝄋 C", "1. Segno C")]
+    #[case("3/4 C", "3/4 C")]
+    #[case("1.  6/8 Segno C", "1. 6/8 Segno C")]
     fn basics(input: &str, output: &str) {
         assert_eq!(Bar::from_str(input).unwrap().to_string(), output);
     }
 
+    #[case("3/5 C", ErrorKind::InvalidTimeSignature("3/5".into()), 0..3)]
+    #[case("1. 0/4 C", ErrorKind::InvalidTimeSignature("0/4".into()), 3..6)]
+    #[case("C 3/5", ErrorKind::InvalidNatural('3'), 2..3)]
+    fn invalid(input: &str, kind: ErrorKind, chars: Range<usize>) {
+        let error = Bar::from_str(input).unwrap_err();
+        assert_eq!(error.kind(), &kind);
+        assert_eq!(error.span().chars(), chars);
+    }
+
+    #[test]
+    fn markers() {
+        let bar = Bar::from_str("Segno C To Coda").unwrap();
+        assert_eq!(bar.markers(), [Marker::Segno, Marker::ToCoda]);
+        assert_eq!(bar.chords().len(), 1);
+        assert_eq!(
+            bar.transpose(&2, &Note::from_str("D").unwrap().scale())
+                .to_string(),
+            "Segno D To Coda"
+        );
+    }
+
     #[test]
     fn tranpose() {
         assert_eq!(
             Bar::from_str("A/D C#m/D")
                 .unwrap()
-                .transpose(&4, &Scale::Minor)
+                .transpose(&4, &Note::from_str("Db").unwrap().scale())
                 .to_string(),
             "Db/Gb Fm/Gb"
         )
@@ -1,77 +1,370 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::{
     bar::Bar,
-    error::Error,
+    chord::Chord,
+    cst::{TokenKind, Tokens},
+    diagnostic::{extend_within, first_error, Diagnostic},
+    error::{offset_in, Error, ErrorKind},
+    navigation::Signs,
+    note::Note,
+    render::{RenderOptions, Spacing},
+    time::TimeSignature,
     transpose::{Scale, Transpose},
 };
 
+/// Stripe around or between bars.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Stripe {
+    /// "|"
+    #[default]
+    Single,
+    /// "|:", start of a repeated part.
+    RepeatStart,
+    /// ":|", back to the last "|:".
+    RepeatEnd,
+    /// ":|:", back to the last "|:", then another repeated part.
+    RepeatBoth,
+}
+
+impl Stripe {
+    fn new(repeat_end: bool, repeat_start: bool) -> Self {
+        match (repeat_end, repeat_start) {
+            (false, false) => Self::Single,
+            (false, true) => Self::RepeatStart,
+            (true, false) => Self::RepeatEnd,
+            (true, true) => Self::RepeatBoth,
+        }
+    }
+
+    pub fn starts_repeat(self) -> bool {
+        matches!(self, Self::RepeatStart | Self::RepeatBoth)
+    }
+
+    pub fn ends_repeat(self) -> bool {
+        matches!(self, Self::RepeatEnd | Self::RepeatBoth)
+    }
+}
+
+impl fmt::Display for Stripe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Single => "|",
+            Self::RepeatStart => "|:",
+            Self::RepeatEnd => ":|",
+            Self::RepeatBoth => ":|:",
+        })
+    }
+}
+
 #[derive(Debug)]
-pub struct BarLine(Vec<Bar>);
+pub struct BarLine<C = Chord> {
+    bars: Vec<Bar<C>>,
+    /// Before, between and after bars: one more than there are bars.
+    stripes: Vec<Stripe>,
+    /// Times the repeat that ends the line is played: "x4" after the last ":|".
+    repeat_count: Option<u32>,
+    /// Change of meter from this line on: "3/4 | C | D |", or a line of its own.
+    time: Option<TimeSignature>,
+}
+
+impl<C> Default for BarLine<C> {
+    fn default() -> Self {
+        Self {
+            bars: Vec::new(),
+            stripes: Vec::new(),
+            repeat_count: None,
+            time: None,
+        }
+    }
+}
 
 impl FromStr for BarLine {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let trimmed = s.trim();
-        if trimmed.is_empty() {
-            return Ok(BarLine(Vec::new()));
-        }
+        Self::parse_with(s, &|s, d| Chord::parse_into(s, &Note::parse_prefix, d))
+    }
+}
+
+impl fmt::Display for BarLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
+impl Transpose for BarLine {
+    fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
+        self.map(&|c| c.transpose(semitone_incr, scale))
+    }
+}
+
+impl<C> BarLine<C> {
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty() && self.time.is_none()
+    }
+
+    pub(crate) fn chords(&self) -> impl Iterator<Item = &C> {
+        self.bars.iter().flat_map(Bar::chords)
+    }
+
+    pub(crate) fn time_signature(&self) -> Option<TimeSignature> {
+        self.time
+    }
+
+    pub(crate) fn bars(&self) -> &[Bar<C>] {
+        &self.bars
+    }
+
+    /// Whether a repeat starts before bar number `index`.
+    pub(crate) fn starts_repeat(&self, index: usize) -> bool {
+        self.stripes[index].starts_repeat()
+    }
+
+    /// Times the repeat that ends after bar number `index` is played, if one ends there.
+    pub(crate) fn ends_repeat(&self, index: usize) -> Option<u32> {
+        let last = index + 1 == self.bars.len();
+        self.stripes[index + 1]
+            .ends_repeat()
+            .then(|| self.repeat_count.filter(|_| last).unwrap_or(2))
+    }
 
-        let mut chars = trimmed.chars();
-        if chars.next() != Some('|') {
-            return Err(Error::BarLineShouldStartWithStripe(s.into()));
+    /// Same bar line with each chord replaced by `f` of it.
+    pub(crate) fn map<D>(&self, f: &impl Fn(&C) -> D) -> BarLine<D> {
+        BarLine {
+            bars: self.bars.iter().map(|b| b.map(f)).collect(),
+            stripes: self.stripes.clone(),
+            repeat_count: self.repeat_count,
+            time: self.time,
         }
-        if chars.next_back() != Some('|') {
-            return Err(Error::BarLineShouldEndWithStripe(s.into()));
+    }
+
+    pub(crate) fn format_with(
+        &self,
+        spacing: Spacing,
+        format_chord: &impl Fn(&C) -> String,
+    ) -> String {
+        // Time signature can be on a line of its own
+        if self.bars.is_empty() {
+            return self.time.map_or_else(String::new, |t| t.to_string());
         }
 
-        let mut vec = Vec::new();
-        for bar in chars.as_str().split('|') {
-            vec.push(Bar::from_str(bar)?);
+        let space = match spacing {
+            Spacing::Normal => " ",
+            Spacing::Compact => "",
+        };
+        let mut line = self
+            .time
+            .map_or_else(String::new, |t| format!("{t}{space}"));
+        line += &self.stripes[0].to_string();
+        for (bar, stripe) in self.bars.iter().zip(&self.stripes[1..]) {
+            // Ending sticks to the stripe before it: "|1. C |". Empty bar keeps both
+            // spaces, "|  |", as one left by a chord that couldn't be read
+            let before = if bar.volta().is_some() { "" } else { space };
+            line += &format!("{before}{}{space}{stripe}", bar.format_with(format_chord));
         }
-        Ok(BarLine(vec))
+        if let Some(count) = self.repeat_count {
+            line += &format!("{space}x{count}");
+        }
+        line
     }
 }
 
-impl ToString for BarLine {
-    fn to_string(&self) -> String {
-        if self.0.is_empty() {
-            return String::new();
+impl BarLine {
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.format_with(options.spacing, &|c| c.render(options))
+    }
+
+    pub(crate) fn parse_with(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+    ) -> Result<Self, Error> {
+        first_error(|diagnostics| {
+            let mut signs = Signs::default();
+            let mut tokens = Tokens::default();
+            let line = Self::parse_into(s, parse_chord, &mut signs, &mut tokens, diagnostics);
+            signs.check(diagnostics);
+            line
+        })
+    }
+
+    /// Parses what it can, reading bars even if stripes around them are missing.
+    /// Repeat signs, endings and markers go to `signs`, to be checked with the rest of the chart,
+    /// and everything read to `tokens`.
+    pub(crate) fn parse_into(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+        signs: &mut Signs,
+        tokens: &mut Tokens,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Self {
+        let (time, trimmed) = split_time(s, s.trim(), tokens, diagnostics);
+        if trimmed.is_empty() {
+            return BarLine {
+                time,
+                ..Default::default()
+            };
         }
+        let (trimmed, repeat_count) = split_repeat_count(s, trimmed, tokens, diagnostics);
+
+        let mut bars = trimmed;
+        let opened = match bars.strip_prefix('|') {
+            Some(rest) => {
+                bars = rest;
+                true
+            }
+            None => {
+                let first = trimmed.chars().next().map_or(0, char::len_utf8);
+                let kind = ErrorKind::BarLineShouldStartWithStripe(s.into());
+                let error = Error::new(kind, trimmed, 0..first);
+                diagnostics.push(error.within(s, trimmed).into());
+                false
+            }
+        };
+        let closed = match bars.strip_suffix('|') {
+            Some(rest) => {
+                bars = rest;
+                true
+            }
+            None => {
+                let last = trimmed.chars().next_back().map_or(0, char::len_utf8);
+                let kind = ErrorKind::BarLineShouldEndWithStripe(s.into());
+                let error = Error::new(kind, trimmed, trimmed.len() - last..trimmed.len());
+                diagnostics.push(error.within(s, trimmed).into());
+                false
+            }
+        };
 
-        let joined_bars = self
-            .0
-            .iter()
-            .map(Bar::to_string)
-            .collect::<Vec<String>>()
-            .join(" | ");
+        let segments: Vec<&str> = bars.split('|').collect();
+        let mut vec = Vec::new();
+        let mut stripes = vec![Stripe::Single];
+        // Bytes of the stripe before the bar, if it's written
+        let mut stripe_bytes = opened.then(|| {
+            let start = offset_in(s, trimmed);
+            start..start + 1
+        });
+        for (index, segment) in segments.iter().enumerate() {
+            // Colons belong to stripes right next to them, if there are any
+            let offset = offset_in(s, segment);
+            let mut bar = *segment;
+            if index > 0 || opened {
+                if let Some(rest) = bar.strip_prefix(':') {
+                    bar = rest;
+                    let stripe = stripes.last_mut().expect("stripe should be before bar");
+                    *stripe = Stripe::new(stripe.ends_repeat(), true);
+                    let kind = ErrorKind::RepeatStartWithoutEnd;
+                    signs.repeat(Error::new(kind, s, offset - 1..offset + 1));
+                    stripe_bytes = stripe_bytes.map(|b| b.start..b.end + 1);
+                }
+            }
+            if let Some(bytes) = stripe_bytes.take() {
+                tokens.push(TokenKind::Stripe, bytes);
+            }
+            let mut repeat_end = None;
+            if index < segments.len() - 1 || closed {
+                let end = offset + segment.len();
+                if let Some(rest) = bar.strip_suffix(':') {
+                    bar = rest;
+                    let kind = ErrorKind::RepeatEndWithoutStart;
+                    repeat_end = Some(Error::new(kind, s, end - 1..end + 1));
+                }
+                let start = if repeat_end.is_some() { end - 1 } else { end };
+                stripe_bytes = Some(start..end + 1);
+            }
+            stripes.push(Stripe::new(repeat_end.is_some(), false));
 
-        format!("| {joined_bars} |")
+            let mut found = Vec::new();
+            let mut bar_signs = Signs::default();
+            let mut bar_tokens = Tokens::default();
+            vec.push(Bar::parse_into(
+                bar,
+                parse_chord,
+                &mut bar_signs,
+                &mut bar_tokens,
+                &mut found,
+            ));
+            extend_within(diagnostics, found, s, bar);
+            signs.append(bar_signs.within(s, bar));
+            tokens.append(bar_tokens.within(s, bar));
+            if let Some(error) = repeat_end {
+                signs.repeat(error);
+            }
+        }
+        if let Some(bytes) = stripe_bytes {
+            tokens.push(TokenKind::Stripe, bytes);
+        }
+        BarLine {
+            bars: vec,
+            stripes,
+            repeat_count,
+            time,
+        }
     }
 }
 
-impl Transpose for BarLine {
-    fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
-        BarLine(
-            self.0
-                .iter()
-                .map(|b| b.transpose(semitone_incr, scale))
-                .collect(),
-        )
+/// Splits "3/4" off "3/4 | C |". Digits and a slash before the first stripe can't be
+/// anything else, so they are reported if they aren't a valid time signature.
+fn split_time<'a>(
+    s: &str,
+    trimmed: &'a str,
+    tokens: &mut Tokens,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (Option<TimeSignature>, &'a str) {
+    let end = trimmed
+        .find(|c: char| c.is_whitespace() || c == '|' || c == ':')
+        .unwrap_or(trimmed.len());
+    let word = &trimmed[..end];
+    if !TimeSignature::looks_like(word) {
+        return (None, trimmed);
+    }
+    let start = offset_in(s, word);
+    tokens.push(TokenKind::TimeSignature, start..start + word.len());
+    let rest = trimmed[end..].trim_start();
+    match TimeSignature::from_str(word) {
+        Ok(time) => (Some(time), rest),
+        Err(err) => {
+            diagnostics.push(err.within(s, word).into());
+            (None, rest)
+        }
     }
 }
 
-impl BarLine {
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+/// Splits "x4" off "|: C :| x4", leaving it in place if there's no ":|" before it.
+fn split_repeat_count<'a>(
+    s: &str,
+    trimmed: &'a str,
+    tokens: &mut Tokens,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (&'a str, Option<u32>) {
+    let stripe = match trimmed.rfind('|') {
+        Some(stripe) if trimmed[..stripe].ends_with(':') => stripe,
+        _ => return (trimmed, None),
+    };
+    let count = trimmed[stripe + 1..].trim_start();
+    let digits = match count.strip_prefix('x') {
+        Some(digits) if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => digits,
+        _ => return (trimmed, None),
+    };
+
+    let start = offset_in(s, count);
+    tokens.push(TokenKind::RepeatCount, start..start + count.len());
+    let bar_line = &trimmed[..=stripe];
+    match digits.parse() {
+        Ok(times) if times >= 2 => (bar_line, Some(times)),
+        _ => {
+            let kind = ErrorKind::InvalidRepeatCount(count.into());
+            let error = Error::new(kind, count, 0..count.len());
+            diagnostics.push(error.within(s, count).into());
+            (bar_line, None)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Scale;
     use super::*;
+    use crate::note::Note;
+    use std::ops::Range;
     use test_case::case;
 
     #[test]
@@ -84,11 +377,90 @@ mod tests {
         test("", "");
     }
 
-    #[case("C", Error::BarLineShouldStartWithStripe("C".into()))]
-    #[case("|C", Error::BarLineShouldEndWithStripe("|C".into()))]
-    #[case("|W|", Error::InvalidNatural('W'))]
-    fn basics_err(input: &str, error: Error) {
-        assert_eq!(BarLine::from_str(input).unwrap_err(), error);
+    #[test]
+    fn compact() {
+        let options = RenderOptions {
+            spacing: Spacing::Compact,
+            ..Default::default()
+        };
+        let render = |input| BarLine::from_str(input).unwrap().render(&options);
+        assert_eq!(render("| C Dm/F# | Bb |"), "|C Dm/F#|Bb|");
+        assert_eq!(render("||"), "||");
+        assert_eq!(render("3/4 | C | 6/8 D |"), "3/4|C|6/8 D|");
+    }
+
+    #[case("3/4 | C | D |", "3/4 | C | D |")]
+    #[case(" 6/8|: C :|", "6/8 |: C :|")]
+    #[case("| 7/8 C | 4/4 D |", "| 7/8 C | 4/4 D |")]
+    #[case("|: C |1. 3/4 D :|", "|: C |1. 3/4 D :|")]
+    #[case("  12/8  ", "12/8")]
+    fn time_signatures(input: &str, output: &str) {
+        assert_eq!(BarLine::from_str(input).unwrap().to_string(), output);
+    }
+
+    #[case("3/5 | C |", 0..3)]
+    #[case(" 0/4", 1..4)]
+    fn time_signatures_err(input: &str, chars: Range<usize>) {
+        let err = BarLine::from_str(input).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::InvalidTimeSignature(input.trim()[..3].into())
+        );
+        assert_eq!(err.span().chars(), chars);
+    }
+
+    #[case("|: C G | Am F :|", "|: C G | Am F :|")]
+    #[case("|:C|D:|:E:|", "|: C | D :|: E :|")]
+    #[case("|: C G | Am F :| x4", "|: C G | Am F :| x4")]
+    #[case("|: C :|x3 ", "|: C :| x3")]
+    fn repeats(input: &str, output: &str) {
+        assert_eq!(BarLine::from_str(input).unwrap().to_string(), output);
+    }
+
+    #[case("|: C |1. D :|2. E |", "|: C |1. D :|2. E |")]
+    #[case("|: C | 1.D | Em :| 2. E |", "|: C |1. D | Em :|2. E |")]
+    #[case("|: C |1,2. D :|3. E | F |", "|: C |1,2. D :|3. E | F |")]
+    #[case("|: C |1. D :|2. E :|3. F |", "|: C |1. D :|2. E :|3. F |")]
+    fn voltas(input: &str, output: &str) {
+        assert_eq!(BarLine::from_str(input).unwrap().to_string(), output);
+    }
+
+    #[test]
+    fn repeats_compact() {
+        let options = RenderOptions {
+            spacing: Spacing::Compact,
+            ..Default::default()
+        };
+        let line = BarLine::from_str("|: C G | Am F :| x4").unwrap();
+        assert_eq!(line.render(&options), "|:C G|Am F:|x4");
+        let line = BarLine::from_str("|: C |1. D :|2. E |").unwrap();
+        assert_eq!(line.render(&options), "|:C|1. D:|2. E|");
+    }
+
+    #[case("|: C | D :| x1", ErrorKind::InvalidRepeatCount("x1".into()), 12..14)]
+    #[case("| C | D | x4", ErrorKind::BarLineShouldEndWithStripe("| C | D | x4".into()), 11..12)]
+    #[case("|: C | D |", ErrorKind::RepeatStartWithoutEnd, 0..2 ; "start without end")]
+    #[case("|: C :|: D |", ErrorKind::RepeatStartWithoutEnd, 6..8)]
+    #[case("| C | D :|", ErrorKind::RepeatEndWithoutStart, 8..10)]
+    #[case("|: C |: D :|", ErrorKind::RepeatStartWithoutEnd, 0..2 ; "nested start")]
+    #[case("| C |1. D |", ErrorKind::VoltaOutsideRepeat, 5..7)]
+    #[case("|: C :|2. D |", ErrorKind::VoltaOutsideRepeat, 7..9)]
+    #[case("|: C |1. D :|2. E :| F :|", ErrorKind::RepeatEndWithoutStart, 23..25)]
+    #[case("| C | G D.S. |", ErrorKind::DalSegnoWithoutSegno, 8..12)]
+    #[case("| C To Coda | G |", ErrorKind::JumpWithoutCoda, 4..11)]
+    #[case("| Coda C | G D.C. al Coda |", ErrorKind::AlCodaWithoutToCoda, 13..25)]
+    #[case("| C | G D.C. al Fine |", ErrorKind::AlFineWithoutFine, 8..20)]
+    fn repeats_err(input: &str, error: ErrorKind, chars: Range<usize>) {
+        let err = BarLine::from_str(input).unwrap_err();
+        assert_eq!(err.kind(), &error);
+        assert_eq!(err.span().chars(), chars);
+    }
+
+    #[case("C", ErrorKind::BarLineShouldStartWithStripe("C".into()))]
+    #[case("|C", ErrorKind::BarLineShouldEndWithStripe("|C".into()))]
+    #[case("|W|", ErrorKind::InvalidNatural('W'))]
+    fn basics_err(input: &str, error: ErrorKind) {
+        assert_eq!(BarLine::from_str(input).unwrap_err().kind(), &error);
     }
 
     #[test]
@@ -96,9 +468,16 @@ mod tests {
         assert_eq!(
             BarLine::from_str("| A/D C#m/D | A/D C#m/D |")
                 .unwrap()
-                .transpose(&4, &Scale::Minor)
+                .transpose(&4, &Note::from_str("Db").unwrap().scale())
                 .to_string(),
             "| Db/Gb Fm/Gb | Db/Gb Fm/Gb |"
-        )
+        );
+        assert_eq!(
+            BarLine::from_str("|: A |1. E :|2. D :| x3")
+                .unwrap()
+                .transpose(&2, &Note::from_str("H").unwrap().scale())
+                .to_string(),
+            "|: H |1. F# :|2. E :| x3"
+        );
     }
 }
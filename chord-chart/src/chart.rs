@@ -1,51 +1,220 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::{
     bar_line::BarLine,
+    chord::Chord,
+    cst::Tokens,
+    detection::{self, KeyCandidate},
+    diagnostic::{first_error, Diagnostic, Warning, WarningKind},
     error::Error,
+    key::Key,
+    nashville,
+    navigation::Signs,
+    note::Note,
+    parse::ParseOptions,
+    render::{RenderOptions, Spacing},
+    roman::{self, RomanNumeral},
+    section::{Label, Section},
+    solfege,
+    time::{self, TimeSignature},
     transpose::{Scale, Transpose},
+    unroll::{self, PlayedBar},
 };
 
-pub struct Chart(Vec<BarLine>);
+/// Chords of a song in sections, bar lines and bars, or what they are analysed as.
+#[derive(Debug)]
+pub struct Chart<C = Chord>(Vec<Section<C>>);
 
 impl FromStr for Chart {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut vec = Vec::new();
-        for line in s.split('\n') {
-            vec.push(BarLine::from_str(line)?);
-        }
-        Ok(Chart(vec))
+        Self::parse_with(s, &|s, d| Chord::parse_into(s, &Note::parse_prefix, d))
+    }
+}
+
+impl fmt::Display for Chart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
     }
 }
 
-impl ToString for Chart {
-    fn to_string(&self) -> String {
+impl fmt::Display for Chart<RomanNumeral> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format_with(Spacing::Normal, &RomanNumeral::to_string))
+    }
+}
+
+impl<C> Chart<C> {
+    /// Song parts in order. Bar lines before the first label make a section without one.
+    pub fn sections(&self) -> &[Section<C>] {
+        &self.0
+    }
+
+    /// Bars in the order they are played. Repeats are played as many times as
+    /// written, or more if their endings are numbered for more passes, endings on
+    /// the passes they are numbered with. ":|" without "|:" goes back to the start
+    /// of the chart.
+    ///
+    /// The first D.C. or D.S. goes back once. After it repeats are played once
+    /// and only the last ending, to the end, "Fine" or "To Coda" and then on from
+    /// the next "Coda". Jumps to markers that aren't there are left out.
+    pub fn unroll(&self) -> Vec<PlayedBar<'_, C>> {
+        unroll::unroll(self)
+    }
+
+    pub(crate) fn chords(&self) -> impl Iterator<Item = &C> {
+        self.0.iter().flat_map(Section::chords)
+    }
+
+    /// Same chart with each chord replaced by `f` of it.
+    pub(crate) fn map<D>(&self, f: &impl Fn(&C) -> D) -> Chart<D> {
+        Chart(self.0.iter().map(|s| s.map(f)).collect())
+    }
+
+    pub(crate) fn format_with(
+        &self,
+        spacing: Spacing,
+        format_chord: &impl Fn(&C) -> String,
+    ) -> String {
         self.0
             .iter()
-            .filter(|l| !l.is_empty())
-            .map(BarLine::to_string)
+            .flat_map(|s| s.format_with(spacing, format_chord))
             .collect::<Vec<String>>()
             .join("\n")
     }
 }
 
+impl Chart {
+    /// Parses chart with notes written in the dialect of `options`.
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_with(s, &|s, d| Chord::parse_options_into(s, options, d))
+    }
+
+    /// Parses what it can instead of stopping at the first error, leaving out
+    /// chords that can't be read. Their bars stay, rendered empty as "|  |" if
+    /// nothing else was read in them. Returns every problem found on the way.
+    pub fn from_str_recovering(s: &str, options: &ParseOptions) -> (Self, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let parse_chord =
+            |s: &str, d: &mut Vec<Diagnostic>| Chord::parse_options_into(s, options, d);
+        let chart = Self::parse_into(s, &parse_chord, &mut diagnostics);
+        (chart, diagnostics)
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.format_with(options.spacing, &|c| c.render(options))
+    }
+
+    pub(crate) fn parse_with(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+    ) -> Result<Self, Error> {
+        first_error(|diagnostics| Self::parse_into(s, parse_chord, diagnostics))
+    }
+
+    pub(crate) fn parse_into(
+        s: &str,
+        parse_chord: &impl Fn(&str, &mut Vec<Diagnostic>) -> Result<Chord, Error>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Self {
+        let mut vec = vec![Section::new(None, 0)];
+        let mut signs = Signs::default();
+        // Newline at the end of the last line doesn't start another one
+        let lines = s.strip_suffix('\n').unwrap_or(s).split('\n');
+        for (index, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                let warning = Warning::new(WarningKind::EmptyLine, line, 0..line.len());
+                diagnostics.push(Diagnostic::from(warning).on_line(index));
+            }
+
+            if let Some(label) = Label::parse(line) {
+                vec.push(Section::new(Some(label), index));
+                continue;
+            }
+
+            let mut found = Vec::new();
+            let mut line_signs = Signs::default();
+            let section = vec.last_mut().expect("chart should start with a section");
+            section.push(BarLine::parse_into(
+                line,
+                parse_chord,
+                &mut line_signs,
+                &mut Tokens::default(),
+                &mut found,
+            ));
+            diagnostics.extend(found.into_iter().map(|d| d.on_line(index)));
+            signs.append(line_signs.on_line(index));
+        }
+        // Repeats and jumps can span bar lines and sections
+        signs.check(diagnostics);
+        // Chart that starts with a label has nothing before it
+        vec.retain(|s| s.label().is_some() || !s.is_empty());
+        Chart(vec)
+    }
+
+    /// Moves the chart from one key to another, spelling notes after the new key.
+    pub fn transpose_to(&self, from: &Key, to: &Key) -> Self {
+        self.transpose(&from.tonic().get_semitones_diff(to.tonic()), &to.scale())
+    }
+
+    /// Time signature the chart starts in: written on its own line before the
+    /// first bars, before their stripe or in the first bar.
+    pub fn time_signature(&self) -> Option<TimeSignature> {
+        time::time_signature(self)
+    }
+
+    /// Scores all 24 major and minor keys by how well chords fit in them,
+    /// most likely key first. Empty for a chart without chords.
+    pub fn detect_key(&self) -> Vec<KeyCandidate> {
+        detection::detect_key(self)
+    }
+
+    /// Renders chords as Nashville numbers: "| C F | Am G/H |" in C is "| 1 4 | 6m 5/7 |".
+    /// Numbers always count from the key tonic, so Am in A minor is "1m" and C is "b3".
+    /// Symbols that would run into the number are put in parentheses: G7 is "5(7)".
+    pub fn to_nashville(&self, key: &Key) -> String {
+        nashville::to_nashville(self, key)
+    }
+
+    /// Reads Nashville numbers back into chords of the key. Parentheses around the
+    /// symbols only part them from the number: "5(7)" is G7 in C. Slash chords are
+    /// read as such even where a time signature could be: "5/4" in a bar is G/F,
+    /// but "6/8" has no bass note 8 and stays a time signature.
+    pub fn from_nashville(s: &str, key: &Key) -> Result<Self, Error> {
+        nashville::from_nashville(s, key)
+    }
+
+    /// Roman numeral analysis keeping the layout of the chart:
+    /// "| Dm7 | G7 | Cmaj7 |" in C is "| ii7 | V7 | Imaj7 |".
+    /// Inversions are shown with figured bass, like "V65", other bass notes as a scale
+    /// degree with a caret, "IV^5", so they don't read as secondary chords like "V/V".
+    pub fn to_roman_numerals(&self, key: &Key) -> Chart<RomanNumeral> {
+        roman::to_roman_numerals(self, key)
+    }
+
+    /// Renders chords with movable-do syllables: "| C F | Am G/H |" in C is "| Do Fa | Lam Sol/Ti |".
+    /// Do is the tonic of relative major, so minor keys are la-based: Am in A minor is "Lam".
+    pub fn to_movable_do(&self, key: &Key, spacing: Spacing) -> String {
+        solfege::to_movable_do(self, key, spacing)
+    }
+}
+
 impl Transpose for Chart {
     fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
-        Chart(
-            self.0
-                .iter()
-                .map(|l| l.transpose(semitone_incr, scale))
-                .collect(),
-        )
+        self.map(&|c| c.transpose(semitone_incr, scale))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Scale;
     use super::*;
+    use crate::error::ErrorKind;
+    use crate::note::Note;
+    use crate::parse::Dialect;
+    use crate::render::{Accidentals, NoteNaming};
+    use std::ops::Range;
+    use test_case::case;
 
     #[test]
     fn basics() {
@@ -62,6 +231,221 @@ mod tests {
         assert_eq!(Chart::from_str(input).unwrap().to_string(), output);
     }
 
+    #[test]
+    fn render() {
+        let chart = Chart::from_str("| G/C | Bm | Bb7 |\n| F#m |").unwrap();
+        let options = RenderOptions {
+            note_naming: NoteNaming::English,
+            accidentals: Accidentals::Unicode,
+            spacing: Spacing::Compact,
+        };
+
+        assert_eq!(chart.render(&options), "|G/C|Bm|B♭7|\n|F♯m|");
+        assert_eq!(chart.render(&RenderOptions::default()), chart.to_string());
+        assert_eq!(
+            Chart::from_str(&chart.render(&options))
+                .unwrap()
+                .to_string(),
+            "| G/C | Hm | Bb7 |\n| F#m |"
+        );
+    }
+
+    #[case("| C | W |", 0, 6..7, 6..7, "W")]
+    #[case("| C |\n| Am | D/Hb |", 1, 9..11, 9..11, "Hb")]
+    #[case("| C |\n\n  C |", 2, 2..3, 2..3, "C")]
+    #[case("| C |\n| C", 1, 2..3, 2..3, "C")]
+    #[case("| C♯ W |", 0, 7..8, 5..6, "W")]
+    fn error_span(input: &str, line: usize, bytes: Range<usize>, chars: Range<usize>, token: &str) {
+        let error = Chart::from_str(input).unwrap_err();
+        let span = error.span();
+        assert_eq!(span.line(), line);
+        assert_eq!(span.bytes(), bytes);
+        assert_eq!(span.chars(), chars);
+        assert_eq!(span.token(), token);
+    }
+
+    #[test]
+    fn recovering() {
+        let input = "| C | W Am |\n| F | G\n  Dm X/C |\n| E |";
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+
+        assert_eq!(chart.to_string(), "| C | Am |\n| F | G |\n| Dm |\n| E |");
+        let errors: Vec<(usize, Range<usize>)> = diagnostics
+            .iter()
+            .map(|d| (d.span().line(), d.span().chars()))
+            .collect();
+        assert_eq!(errors, [(0, 6..7), (1, 6..7), (2, 2..3), (2, 5..6)]);
+        assert!(diagnostics.iter().all(Diagnostic::is_error));
+    }
+
+    #[test]
+    fn warnings() {
+        let input = "| C/ | Dm/Fm |\n\n| G |";
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+
+        assert_eq!(chart.to_string(), "| C | Dm/F |\n| G |");
+        let warnings: Vec<(usize, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.span().line(), d.span().token()))
+            .collect();
+        assert_eq!(warnings, [(0, "/"), (0, "m"), (1, "")]);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning[W002]: empty bass note at line 1, column 4"
+        );
+        assert!(!diagnostics.iter().any(Diagnostic::is_error));
+        assert!(Chart::from_str(input).is_ok());
+    }
+
+    #[case("| C |\n", 0 ; "trailing newline")]
+    #[case("| C |\n\n", 1 ; "empty line before trailing newline")]
+    fn trailing_newline(input: &str, warnings: usize) {
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+        assert_eq!(chart.to_string(), "| C |");
+        assert_eq!(diagnostics.len(), warnings);
+    }
+
+    #[test]
+    fn sections() {
+        let input = "[Intro]\n| Am | C |\n\nVerse 1:\n| F | G |\n| Am |\n[Outro]";
+        let chart = Chart::from_str(input).unwrap();
+
+        assert_eq!(
+            chart.to_string(),
+            "[Intro]\n| Am | C |\nVerse 1:\n| F | G |\n| Am |\n[Outro]"
+        );
+        let sections: Vec<(Option<&str>, String)> = chart
+            .sections()
+            .iter()
+            .map(|s| (s.label(), s.to_string()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (Some("Intro"), "| Am | C |".into()),
+                (Some("Verse 1"), "| F | G |\n| Am |".into()),
+                (Some("Outro"), "".into()),
+            ]
+        );
+        assert_eq!(
+            chart
+                .transpose_to(&Key::from_str("Am").unwrap(), &Key::from_str("Em").unwrap())
+                .to_string(),
+            "[Intro]\n| Em | G |\nVerse 1:\n| C | D |\n| Em |\n[Outro]"
+        );
+    }
+
+    #[test]
+    fn repeats() {
+        let input = "|: C | G |\n| Am | F :| x3\n| Dm :|";
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+
+        assert_eq!(chart.to_string(), "|: C | G |\n| Am | F :| x3\n| Dm :|");
+        let errors: Vec<(&ErrorKind, usize, Range<usize>)> = diagnostics
+            .iter()
+            .filter_map(|d| match d {
+                Diagnostic::Error(e) => Some((e.kind(), e.span().line(), e.span().chars())),
+                Diagnostic::Warning(_) => None,
+            })
+            .collect();
+        assert_eq!(errors, [(&ErrorKind::RepeatEndWithoutStart, 2, 5..7)]);
+        assert!(Chart::from_str("[Verse]\n|: C |\n[Chorus]\n| F :|").is_ok());
+    }
+
+    #[test]
+    fn voltas() {
+        let input = "|: C | G |\n|1. Am | F :|\n|2. Dm |\n| E |2. A |";
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+
+        assert_eq!(chart.to_string(), input);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "E014");
+        assert_eq!(diagnostics[0].span().line(), 3);
+        assert_eq!(diagnostics[0].span().token(), "2.");
+    }
+
+    #[test]
+    fn time_signatures() {
+        let input = "4/4\n[Verse]\n| C | G |\n3/4 | Am | 6/8 F |";
+        let chart = Chart::from_str(input).unwrap();
+        assert_eq!(chart.to_string(), input);
+        assert_eq!(
+            chart
+                .transpose_to(&Key::from_str("C").unwrap(), &Key::from_str("D").unwrap())
+                .to_string(),
+            "4/4\n[Verse]\n| D | A |\n3/4 | Hm | 6/8 G |"
+        );
+        let error = Chart::from_str("| C |\n3/5 | D |").unwrap_err();
+        assert_eq!(error.kind().code(), "E019");
+        assert_eq!(error.span().line(), 1);
+    }
+
+    #[test]
+    fn markers() {
+        let input =
+            "| C | Segno G To Coda |\n| Am D.S. al Coda |\n[Coda]\n| Coda F | C D.S. al Fine |";
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &ParseOptions::default());
+
+        assert_eq!(chart.to_string(), input);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "E018");
+        assert_eq!(diagnostics[0].span().line(), 3);
+        assert_eq!(diagnostics[0].span().token(), "D.S. al Fine");
+    }
+
+    #[test]
+    fn section_without_label() {
+        let chart = Chart::from_str("| C |\n[Chorus]\n| F |").unwrap();
+        let labels: Vec<Option<&str>> = chart.sections().iter().map(Section::label).collect();
+        assert_eq!(labels, [None, Some("Chorus")]);
+    }
+
+    #[test]
+    fn strict() {
+        let input = "| C | Gmajj7 | Am7 |";
+        let options = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+
+        let error = Chart::from_str_with(input, &options).unwrap_err();
+        assert_eq!(error.span().token(), "j");
+        // Bar of the rejected chord stays, empty, so bars after it keep their place
+        let (chart, diagnostics) = Chart::from_str_recovering(input, &options);
+        assert_eq!(chart.to_string(), "| C |  | Am7 |");
+        let bars = chart.unroll();
+        assert_eq!(bars.len(), 3);
+        assert!(bars[1].bar().chords().is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(Chart::from_str(input).is_ok());
+    }
+
+    #[test]
+    fn dialect() {
+        let options = ParseOptions {
+            dialect: Dialect::German,
+            ..Default::default()
+        };
+        let chart = Chart::from_str_with("| B Es | Fism/Cis | H7 |", &options).unwrap();
+        assert_eq!(chart.to_string(), "| Bb Eb | F#m/C# | H7 |");
+    }
+
+    #[test]
+    fn solfege() {
+        let options = ParseOptions {
+            dialect: Dialect::Solfege,
+            ..Default::default()
+        };
+        let chart = Chart::from_str_with("| Do Lam | Sol#m7/Si | Fa |", &options).unwrap();
+        assert_eq!(chart.to_string(), "| C Am | G#m7/H | F |");
+
+        let options = RenderOptions {
+            note_naming: NoteNaming::Solfege,
+            ..Default::default()
+        };
+        assert_eq!(chart.render(&options), "| Do Lam | Sol#m7/Si | Fa |");
+    }
+
     #[test]
     fn tranpose() {
         let input = r#"
@@ -72,12 +456,29 @@ mod tests {
 
         | Bm | A | G |
         "#;
-        let output = "| H/E | Ebm | Db |\n| Gb |\n| Db |\n| Ebm | Db | H |";
+        let output = "| Cb/Fb | Ebm | Db |\n| Gb |\n| Db |\n| Ebm | Db | Cb |";
+
+        assert_eq!(
+            Chart::from_str(input)
+                .unwrap()
+                .transpose(&4, &Note::from_str("Db").unwrap().scale())
+                .to_string(),
+            output
+        )
+    }
+
+    #[test]
+    fn transpose_to() {
+        let input = "| Am | Dm | E7 | Am |";
+        let output = "| F#m | Hm | C#7 | F#m |";
 
         assert_eq!(
             Chart::from_str(input)
                 .unwrap()
-                .transpose(&4, &Scale::Minor)
+                .transpose_to(
+                    &Key::from_str("Am").unwrap(),
+                    &Key::from_str("F#m").unwrap()
+                )
                 .to_string(),
             output
         )
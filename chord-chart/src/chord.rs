@@ -1,12 +1,17 @@
+use crate::diagnostic::{Diagnostic, Warning, WarningKind};
 use crate::error::Error;
-use crate::note::{Accidental, Note};
+use crate::note::Note;
+use crate::parse::ParseOptions;
+use crate::quality::{ChordQuality, Triad};
+use crate::render::RenderOptions;
 use crate::transpose::{Scale, Transpose};
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Chord {
     note: Note,
     symbols: String,
+    quality: ChordQuality,
     bass_note: Option<Note>,
 }
 
@@ -15,89 +20,199 @@ impl Chord {
         Self {
             note,
             symbols: symbols.to_owned(),
+            quality: ChordQuality::parse(symbols),
             bass_note,
         }
     }
-}
 
-impl FromStr for Chord {
-    type Err = Error;
+    pub fn root(&self) -> &Note {
+        &self.note
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
+    pub fn bass_note(&self) -> Option<&Note> {
+        self.bass_note.as_ref()
+    }
 
-        let (natural_ch, accidental_ch) = (chars.next(), chars.next());
+    pub fn quality(&self) -> &ChordQuality {
+        &self.quality
+    }
 
-        let note = Note::parse(natural_ch, accidental_ch)?;
+    /// Constituent notes starting from the bass, "Dm7/C" -> C D F A.
+    pub fn notes(&self) -> Vec<Note> {
+        let mut notes: Vec<Note> = self
+            .quality
+            .intervals()
+            .into_iter()
+            .map(|(steps, semitones)| self.note.add_interval(steps, semitones))
+            .collect();
 
-        let mut remaining_symbols = String::new();
-        if note.accidental() == &Accidental::Natural {
-            if let Some(ch) = accidental_ch {
-                // Accidental char wasn't actually an accidental
-                remaining_symbols.push(ch)
-            }
+        if let Some(bass_note) = &self.bass_note {
+            notes.retain(|n| n.semitone() != bass_note.semitone());
+            notes.insert(0, bass_note.clone());
         }
-        remaining_symbols.push_str(chars.as_str());
-
-        let mut building_bass_note = false;
-        let mut bass_note_value = String::new();
-        let mut symbols = String::new();
-
-        for char in remaining_symbols.chars() {
-            if building_bass_note {
-                bass_note_value.push(char);
-            } else if char == '/' {
-                building_bass_note = true;
-            } else {
-                symbols.push(char);
-            }
+        notes
+    }
+
+    /// Symbols as written between the root and the bass note, "m7" in "Am7/G".
+    pub(crate) fn symbols(&self) -> &str {
+        &self.symbols
+    }
+
+    /// Same chord with other symbols.
+    pub(crate) fn with_symbols(&self, symbols: &str) -> Self {
+        Self::new(self.note.clone(), symbols, self.bass_note.clone())
+    }
+
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_options_into(s, options, &mut Vec::new())
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.format_with(&|n| n.render(options))
+    }
+
+    /// Parses chord with root and bass notes written as `parse_note` expects them.
+    pub(crate) fn parse_with(
+        s: &str,
+        parse_note: &impl Fn(&str) -> Result<(Note, &str), Error>,
+    ) -> Result<Self, Error> {
+        Self::parse_into(s, parse_note, &mut Vec::new())
+    }
+
+    /// Like `parse_with`, but warns about parts of the bass that are dropped.
+    pub(crate) fn parse_into(
+        s: &str,
+        parse_note: &impl Fn(&str) -> Result<(Note, &str), Error>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Self, Error> {
+        let (note, symbols, bass_note) = Self::parse_parts(s, parse_note, diagnostics)?;
+        Ok(Chord::new(note, symbols, bass_note))
+    }
+
+    /// Parses chord written as `options` say, checking its symbols in strict mode.
+    pub(crate) fn parse_options_into(
+        s: &str,
+        options: &ParseOptions,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Self, Error> {
+        let (note, symbols, bass_note) =
+            Self::parse_parts(s, &|s| Note::parse_prefix_with(s, options), diagnostics)?;
+        if options.strict {
+            ChordQuality::parse_strict(symbols).map_err(|e| e.within(s, symbols))?;
         }
+        Ok(Chord::new(note, symbols, bass_note))
+    }
+
+    /// Splits chord into root, symbols and bass note.
+    fn parse_parts<'a>(
+        s: &'a str,
+        parse_note: &impl Fn(&str) -> Result<(Note, &str), Error>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<(Note, &'a str, Option<Note>), Error> {
+        let (note, remaining_symbols) = parse_note(s)?;
+        let (symbols, bass_note_value) = match Self::bass_slash(remaining_symbols, parse_note) {
+            Some(index) => (&remaining_symbols[..index], &remaining_symbols[index + 1..]),
+            None => (remaining_symbols, ""),
+        };
 
         let bass_note = if bass_note_value.is_empty() {
+            if remaining_symbols.ends_with('/') {
+                let warning = Warning::new(WarningKind::EmptyBassNote, s, s.len() - 1..s.len());
+                diagnostics.push(warning.into());
+            }
             None
         } else {
-            let mut chars = bass_note_value.chars();
-
-            match Note::parse(chars.next(), chars.next()) {
-                Ok(note) => Some(note),
-                Err(Error::NoNatural) => None,
-                Err(err) => return Err(err),
+            match parse_note(bass_note_value) {
+                Ok((note, rest)) => {
+                    if !rest.is_empty() {
+                        let kind = WarningKind::IgnoredAfterNote(rest.into());
+                        diagnostics
+                            .push(Warning::new(kind, s, s.len() - rest.len()..s.len()).into());
+                    }
+                    Some(note)
+                }
+                Err(err) => return Err(err.within(s, bass_note_value)),
             }
         };
 
-        Ok(Chord::new(note, &symbols, bass_note))
+        Ok((note, symbols, bass_note))
+    }
+
+    /// Finds the slash before the bass note. Slashes followed by a number that
+    /// isn't a note are part of the symbols: "C6/9", "G7/6", and so are the ones
+    /// in parentheses: "1(7/6)".
+    fn bass_slash(
+        symbols: &str,
+        parse_note: &impl Fn(&str) -> Result<(Note, &str), Error>,
+    ) -> Option<usize> {
+        let mut depth = 0;
+        symbols.char_indices().find_map(|(index, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                '/' if depth <= 0 => {
+                    let bass = &symbols[index + 1..];
+                    if !bass.starts_with(|c: char| c.is_ascii_digit()) || parse_note(bass).is_ok() {
+                        return Some(index);
+                    }
+                }
+                _ => (),
+            }
+            None
+        })
     }
-}
 
-impl ToString for Chord {
-    fn to_string(&self) -> String {
-        let note = self.note.to_string();
+    pub(crate) fn format_with(&self, format_note: &impl Fn(&Note) -> String) -> String {
+        let note = format_note(&self.note);
 
         if let Some(bass_note) = &self.bass_note {
-            note + &self.symbols + "/" + &bass_note.to_string()
+            note + &self.symbols + "/" + &format_note(bass_note)
         } else {
             note + &self.symbols
         }
     }
 }
 
+impl FromStr for Chord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &Note::parse_prefix)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
 impl Transpose for Chord {
     fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
-        Self::new(
-            Note::transpose(&self.note, semitone_incr, scale),
-            &self.symbols,
-            self.bass_note
-                .as_ref()
-                .map(|note| note.transpose(semitone_incr, scale)),
-        )
+        let semitone = self.note.semitone() + semitone_incr;
+        let note = if self.quality.triad() == Triad::Diminished {
+            scale.spell_raised(semitone)
+        } else {
+            scale.spell(semitone)
+        };
+
+        // Bass keeps its function relative to the root: E/G# in C is not E/Ab
+        let bass_note = self.bass_note.as_ref().map(|bass_note| {
+            note.try_add_interval(
+                self.note.steps_to(bass_note),
+                self.note.get_semitones_diff(bass_note),
+            )
+            .unwrap_or_else(|| bass_note.transpose(semitone_incr, scale))
+        });
+
+        Self::new(note, &self.symbols, bass_note)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Error::*;
-    use super::Scale::*;
     use super::*;
+    use crate::error::ErrorKind::{self, *};
     use test_case::case;
 
     #[case("A", "A")]
@@ -108,28 +223,128 @@ mod tests {
     #[case("C/db", "C/Db")]
     #[case("C/D", "C/D")]
     #[case("C/", "C")]
+    #[case("Cbmaj7/Eb", "Cbmaj7/Eb")]
+    #[case("E#m/B#", "E#m/B#")]
+    #[case("Fxdim", "F##dim")]
+    #[case("Abb7", "Abb7")]
     fn basics_ok(input: &str, output: &str) {
         assert_eq!(Chord::from_str(input).unwrap().to_string(), output);
     }
 
     #[case("w", InvalidNatural('w'))]
-    #[case("E#", InvalidNote("E#"))]
-    #[case("C/E#", InvalidNote("E#"))]
+    #[case("Hbm", InvalidNote("Hb"))]
+    #[case("C/H#", InvalidNote("H#"))]
     #[case("C/W#", InvalidNatural('W'))]
     #[case("A//", InvalidNatural('/'))]
-    fn basics_err(input: &str, error: Error) {
-        assert_eq!(Chord::from_str(input).unwrap_err(), error);
+    fn basics_err(input: &str, error: ErrorKind) {
+        assert_eq!(Chord::from_str(input).unwrap_err().kind(), &error);
+    }
+
+    #[case("C#m/Dbm", WarningKind::IgnoredAfterNote("m".into()), 6..7)]
+    #[case("C/", WarningKind::EmptyBassNote, 1..2)]
+    #[case("Am7/Gsus", WarningKind::IgnoredAfterNote("sus".into()), 5..8)]
+    fn warnings(input: &str, kind: WarningKind, bytes: std::ops::Range<usize>) {
+        let mut diagnostics = Vec::new();
+        Chord::parse_into(input, &Note::parse_prefix, &mut diagnostics).unwrap();
+        match diagnostics.as_slice() {
+            [Diagnostic::Warning(warning)] => {
+                assert_eq!(warning.kind(), &kind);
+                assert_eq!(warning.span().bytes(), bytes);
+            }
+            _ => panic!("expected one warning, got {diagnostics:?}"),
+        }
+    }
+
+    #[case("Cabc", "abc", 1..2)]
+    #[case("Gmajj7/B", "majj7", 4..5)]
+    #[case("A(((", "(((", 1..2)]
+    fn strict(input: &str, symbols: &str, bytes: std::ops::Range<usize>) {
+        let options = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let error = Chord::from_str_with(input, &options).unwrap_err();
+        assert_eq!(error.kind(), &InvalidQuality(symbols.into()));
+        assert_eq!(error.span().bytes(), bytes);
+        assert!(Chord::from_str(input).is_ok());
+    }
+
+    #[test]
+    fn strict_ok() {
+        let options = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let chord = Chord::from_str_with("Am7(b5)/G", &options).unwrap();
+        assert_eq!(chord.to_string(), "Am7(b5)/G");
+    }
+
+    #[case("C6/9", None)]
+    #[case("G7/6", None)]
+    #[case("C/9", None)]
+    #[case("C6/9/E", Some("E"))]
+    #[case("Am(maj7)/G", Some("G"))]
+    #[case("F/A", Some("A"))]
+    fn slash_in_symbols(input: &str, bass_note: Option<&str>) {
+        let chord = Chord::from_str(input).unwrap();
+        assert_eq!(chord.to_string(), input);
+        assert_eq!(chord.bass_note().map(Note::to_string).as_deref(), bass_note);
+    }
+
+    #[case("Cmaj7", "CM7")]
+    #[case("Cmaj7", "CΔ7")]
+    #[case("Am7b5/G", "Aø7/G")]
+    fn quality(first: &str, second: &str) {
+        assert_eq!(
+            Chord::from_str(first).unwrap().quality(),
+            Chord::from_str(second).unwrap().quality()
+        );
+    }
+
+    #[case("C", "C E G")]
+    #[case("Dm7/C", "C D F A")]
+    #[case("C/D", "D C E G")]
+    #[case("Ebmaj7", "Eb G Bb D")]
+    #[case("F#m7b5", "F# A C E")]
+    #[case("Bb7b9", "Bb D F Ab Cb")]
+    #[case("G13", "G H D F A E")]
+    #[case("Asus4", "A D E")]
+    #[case("C69", "C E G A D")]
+    #[case("C6/9", "C E G A D")]
+    #[case("C6/9/E", "E C G A D")]
+    #[case("G7/6", "G H D E F")]
+    #[case("E5", "E H")]
+    #[case("Caug", "C E G#")]
+    #[case("Cdim7", "C Eb Gb Bbb")]
+    #[case("E+", "E G# B#")]
+    #[case("C#aug", "C# E# G##")]
+    fn notes(input: &str, output: &str) {
+        let notes: Vec<String> = Chord::from_str(input)
+            .unwrap()
+            .notes()
+            .iter()
+            .map(Note::to_string)
+            .collect();
+        assert_eq!(notes.join(" "), output);
     }
 
-    #[case("A", 1, Major, "A#")]
-    #[case("C#m/D", 1, Major, "Dm/D#")]
-    #[case("C#m/D", 12, Major, "C#m/D")]
-    #[case("A/D", 4, Minor, "Db/Gb")]
-    fn tranpose(input: &str, semitone_incr: i32, scale: Scale, output: &str) {
+    #[case("A", 1, "F#", "A#")]
+    #[case("C#m/D", 1, "A", "Dm/Eb")]
+    #[case("C#m/D", 12, "E", "C#m/D")]
+    #[case("A/D", 4, "Db", "Db/Gb")]
+    #[case("C/E", 4, "E", "E/G#")]
+    #[case("G7/F", 3, "Eb", "Bb7/Ab")]
+    #[case("H", 9, "C", "Ab")]
+    #[case("Hdim7", 9, "C", "G#dim7")]
+    #[case("Adim", 4, "C", "C#dim")]
+    #[case("C", 11, "Gb", "Cb")]
+    #[case("C/E", 1, "C#", "C#/E#")]
+    #[case("Caug", 6, "Gb", "Gbaug")]
+    fn tranpose(input: &str, semitone_incr: i32, key: &str, output: &str) {
         assert_eq!(
             Chord::from_str(input)
                 .unwrap()
-                .transpose(&semitone_incr, &scale)
+                .transpose(&semitone_incr, &Note::from_str(key).unwrap().scale())
                 .to_string(),
             output
         )
@@ -0,0 +1,507 @@
+use std::{fmt, ops::Range};
+
+use crate::{
+    bar_line::BarLine,
+    chart::Chart,
+    chord::Chord,
+    diagnostic::Diagnostic,
+    error::{offset_in, Error},
+    key::Key,
+    navigation::Signs,
+    parse::ParseOptions,
+    render::RenderOptions,
+    section::Label,
+    transpose::Transpose,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenKind {
+    /// "|", or a repeat sign: "|:", ":|" or ":|:".
+    Stripe,
+    Chord,
+    /// "x4" after a repeat sign.
+    RepeatCount,
+    /// Ending at the start of a bar: "1." or "1,2.".
+    Volta,
+    /// Navigation marker: "Segno", "To Coda", "D.S. al Fine" and so on.
+    Marker,
+    /// "3/4" before the first stripe of a line or first in a bar.
+    TimeSignature,
+    /// Section label: "[Chorus]" or "Chorus:".
+    Label,
+    /// Spaces and tabs inside a line.
+    Whitespace,
+    Newline,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Token {
+    kind: TokenKind,
+    line: usize,
+    bytes: Range<usize>,
+}
+
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    /// Line of the chart, counting from zero.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Where the token is in the whole source.
+    pub fn bytes(&self) -> Range<usize> {
+        self.bytes.clone()
+    }
+
+    /// Whitespace and newlines that don't change the chart.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace | TokenKind::Newline)
+    }
+}
+
+/// Tokens found while parsing, as bytes of the text parsed.
+#[derive(Debug, Default)]
+pub(crate) struct Tokens(Vec<(TokenKind, Range<usize>)>);
+
+impl Tokens {
+    pub(crate) fn push(&mut self, kind: TokenKind, bytes: Range<usize>) {
+        self.0.push((kind, bytes));
+    }
+
+    pub(crate) fn append(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    /// Moves tokens found in `part` to where `part` is in `s`.
+    pub(crate) fn within(mut self, s: &str, part: &str) -> Self {
+        let offset = offset_in(s, part);
+        for (_, bytes) in &mut self.0 {
+            *bytes = bytes.start + offset..bytes.end + offset;
+        }
+        self
+    }
+}
+
+/// Lossless syntax of a chart: every byte of the source belongs to one token,
+/// so chords can be changed without reformatting anything else.
+#[derive(Debug, Clone)]
+pub struct SyntaxTree {
+    source: String,
+    tokens: Vec<Token>,
+    options: ParseOptions,
+}
+
+impl SyntaxTree {
+    pub fn parse(s: &str) -> Self {
+        Self::parse_with(s, &ParseOptions::default())
+    }
+
+    /// Tokenizes `s`, remembering how its chords should be read.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = 0;
+        for (line, text) in s.split('\n').enumerate() {
+            if line > 0 {
+                tokens.push(Token {
+                    kind: TokenKind::Newline,
+                    line: line - 1,
+                    bytes: start - 1..start,
+                });
+            }
+            tokenize_line(text, line, start, options, &mut tokens);
+            start += text.len() + 1;
+        }
+        Self {
+            source: s.to_owned(),
+            tokens,
+            options: *options,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn text(&self, token: &Token) -> &str {
+        &self.source[token.bytes()]
+    }
+
+    /// Semantic chart, as `Chart::from_str_with` reads the source.
+    pub fn chart(&self) -> Result<Chart, Error> {
+        Chart::from_str_with(&self.source, &self.options)
+    }
+
+    /// Chords in order, each one read on its own.
+    pub fn chords(&self) -> impl Iterator<Item = Result<Chord, Error>> + '_ {
+        self.tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Chord)
+            .map(|t| self.parse_chord(t))
+    }
+
+    /// Rewrites every chord with `f`, keeping the rest of the source byte for byte.
+    /// Chords that `f` leaves as they are keep their text.
+    pub fn map_chords(
+        &self,
+        options: &RenderOptions,
+        mut f: impl FnMut(Chord) -> Chord,
+    ) -> Result<Self, Error> {
+        let mut source = String::with_capacity(self.source.len());
+        for token in &self.tokens {
+            if token.kind != TokenKind::Chord {
+                source += self.text(token);
+                continue;
+            }
+            let mapped = f(self.parse_chord(token)?);
+            if self.parse_chord(token)? == mapped {
+                source += self.text(token);
+            } else {
+                source += &mapped.render(options);
+            }
+        }
+        Ok(Self::parse_with(&source, &self.options))
+    }
+
+    /// Replaces chord number `index`, counting from zero, unless it's the same chord.
+    pub fn replace_chord(
+        &self,
+        index: usize,
+        chord: &Chord,
+        options: &RenderOptions,
+    ) -> Option<Self> {
+        let token = self
+            .tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Chord)
+            .nth(index)?;
+        if self.parse_chord(token).is_ok_and(|c| &c == chord) {
+            return Some(self.clone());
+        }
+        let source = self.source[..token.bytes.start].to_owned()
+            + &chord.render(options)
+            + &self.source[token.bytes.end..];
+        Some(Self::parse_with(&source, &self.options))
+    }
+
+    /// Moves chords from one key to another, leaving spacing as it is.
+    pub fn transpose_to(
+        &self,
+        from: &Key,
+        to: &Key,
+        options: &RenderOptions,
+    ) -> Result<Self, Error> {
+        let semitone_incr = from.tonic().get_semitones_diff(to.tonic());
+        let scale = to.scale();
+        self.map_chords(options, |c| c.transpose(&semitone_incr, &scale))
+    }
+
+    fn parse_chord(&self, token: &Token) -> Result<Chord, Error> {
+        let line_start = self.source[..token.bytes.start]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let line = &self.source[line_start..];
+        let text = self.text(token);
+        Chord::from_str_with(text, &self.options)
+            .map_err(|e| e.within(line, text).on_line(token.line))
+    }
+}
+
+/// Prints the source as it was read.
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Reads the line as `Chart` does, with whitespace between what it reads.
+fn tokenize_line(
+    text: &str,
+    line: usize,
+    offset: usize,
+    options: &ParseOptions,
+    tokens: &mut Vec<Token>,
+) {
+    let mut found = Tokens::default();
+    if Label::parse(text).is_some() {
+        let start = text.len() - text.trim_start().len();
+        found.push(TokenKind::Label, start..text.trim_end().len());
+    } else {
+        let parse_chord =
+            |s: &str, d: &mut Vec<Diagnostic>| Chord::parse_options_into(s, options, d);
+        let mut signs = Signs::default();
+        BarLine::parse_into(text, &parse_chord, &mut signs, &mut found, &mut Vec::new());
+    }
+    found.0.sort_by_key(|(_, bytes)| bytes.start);
+
+    let mut push = |kind, bytes: Range<usize>| {
+        if !bytes.is_empty() {
+            tokens.push(Token {
+                kind,
+                line,
+                bytes: offset + bytes.start..offset + bytes.end,
+            });
+        }
+    };
+    let mut start = 0;
+    for (kind, bytes) in found.0 {
+        push(TokenKind::Whitespace, start..bytes.start);
+        start = bytes.end;
+        push(kind, bytes);
+    }
+    push(TokenKind::Whitespace, start..text.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::parse::Dialect;
+    use crate::render::NoteNaming;
+    use std::str::FromStr;
+
+    const SOURCE: &str = "|  Am   |C/E  G |\n\n\t| F |  \r\n";
+
+    #[test]
+    fn lossless() {
+        let tree = SyntaxTree::parse(SOURCE);
+        assert_eq!(tree.to_string(), SOURCE);
+        let text: String = tree.tokens().iter().map(|t| tree.text(t)).collect();
+        assert_eq!(text, SOURCE);
+    }
+
+    #[test]
+    fn tokens() {
+        let tree = SyntaxTree::parse("| C  G/H | ");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Chord, "C"),
+                (TokenKind::Whitespace, "  "),
+                (TokenKind::Chord, "G/H"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Whitespace, " "),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeats() {
+        let tree = SyntaxTree::parse("|:C :|: D:| x3");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Stripe, "|:"),
+                (TokenKind::Chord, "C"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Stripe, ":|:"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Chord, "D"),
+                (TokenKind::Stripe, ":|"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::RepeatCount, "x3"),
+            ]
+        );
+        assert_eq!(tree.chart().unwrap().to_string(), "|: C :|: D :| x3");
+    }
+
+    #[test]
+    fn voltas() {
+        let tree = SyntaxTree::parse("|: C |1.D :| 2. E |");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .filter(|t| !t.is_trivia())
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Stripe, "|:"),
+                (TokenKind::Chord, "C"),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Volta, "1."),
+                (TokenKind::Chord, "D"),
+                (TokenKind::Stripe, ":|"),
+                (TokenKind::Volta, "2."),
+                (TokenKind::Chord, "E"),
+                (TokenKind::Stripe, "|"),
+            ]
+        );
+        let chords: Vec<String> = tree.chords().map(|c| c.unwrap().to_string()).collect();
+        assert_eq!(chords, ["C", "D", "E"]);
+    }
+
+    #[test]
+    fn time_signatures() {
+        let tree = SyntaxTree::parse("3/4\n6/8 | C 3/4 |1. 7/8 D |");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .filter(|t| !t.is_trivia())
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::TimeSignature, "3/4"),
+                (TokenKind::TimeSignature, "6/8"),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Chord, "C"),
+                (TokenKind::Chord, "3/4"),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Volta, "1."),
+                (TokenKind::TimeSignature, "7/8"),
+                (TokenKind::Chord, "D"),
+                (TokenKind::Stripe, "|"),
+            ]
+        );
+    }
+
+    #[test]
+    fn markers() {
+        let tree = SyntaxTree::parse("| Segno C | G To  Coda|Coda Am D.S. al Coda |");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .filter(|t| !t.is_trivia())
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Marker, "Segno"),
+                (TokenKind::Chord, "C"),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Chord, "G"),
+                (TokenKind::Marker, "To  Coda"),
+                (TokenKind::Stripe, "|"),
+                (TokenKind::Marker, "Coda"),
+                (TokenKind::Chord, "Am"),
+                (TokenKind::Marker, "D.S. al Coda"),
+                (TokenKind::Stripe, "|"),
+            ]
+        );
+        let chords: Vec<String> = tree.chords().map(|c| c.unwrap().to_string()).collect();
+        assert_eq!(chords, ["C", "G", "Am"]);
+    }
+
+    #[test]
+    fn label() {
+        let tree = SyntaxTree::parse(" [Verse 1] \n| C |");
+        let tokens: Vec<(TokenKind, &str)> = tree
+            .tokens()
+            .iter()
+            .take(3)
+            .map(|t| (t.kind(), tree.text(t)))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Label, "[Verse 1]"),
+                (TokenKind::Whitespace, " "),
+            ]
+        );
+        assert_eq!(tree.chords().count(), 1);
+    }
+
+    #[test]
+    fn chart() {
+        let tree = SyntaxTree::parse(SOURCE);
+        assert_eq!(tree.chart().unwrap().to_string(), "| Am | C/E G |\n| F |");
+        assert_eq!(tree.chords().count(), 4);
+    }
+
+    #[test]
+    fn transpose_to() {
+        let tree = SyntaxTree::parse(SOURCE);
+        let options = RenderOptions {
+            note_naming: NoteNaming::English,
+            ..Default::default()
+        };
+        let transposed = tree
+            .transpose_to(
+                &Key::from_str("Am").unwrap(),
+                &Key::from_str("Hm").unwrap(),
+                &options,
+            )
+            .unwrap();
+        assert_eq!(
+            transposed.to_string(),
+            "|  Bm   |D/F#  A |\n\n\t| G |  \r\n"
+        );
+    }
+
+    #[test]
+    fn identity() {
+        let source = "| Bm  A/C# |\n|F#7   Hm |";
+        let tree = SyntaxTree::parse(source);
+        let mapped = tree.map_chords(&RenderOptions::default(), |c| c).unwrap();
+        assert_eq!(mapped.to_string(), source);
+        let key = Key::from_str("D").unwrap();
+        let transposed = tree
+            .transpose_to(&key, &key, &RenderOptions::default())
+            .unwrap();
+        assert_eq!(transposed.to_string(), source);
+    }
+
+    #[test]
+    fn replace_chord() {
+        let tree = SyntaxTree::parse("|C   D|");
+        let chord = Chord::from_str("Dm7").unwrap();
+        let replaced = tree
+            .replace_chord(1, &chord, &RenderOptions::default())
+            .unwrap();
+        assert_eq!(replaced.to_string(), "|C   Dm7|");
+        assert!(tree
+            .replace_chord(2, &chord, &RenderOptions::default())
+            .is_none());
+        let tree = SyntaxTree::parse("|Bm   D|");
+        let same = Chord::from_str("Bm").unwrap();
+        let replaced = tree
+            .replace_chord(0, &same, &RenderOptions::default())
+            .unwrap();
+        assert_eq!(replaced.to_string(), "|Bm   D|");
+    }
+
+    #[test]
+    fn dialect() {
+        let options = ParseOptions {
+            dialect: Dialect::German,
+            ..Default::default()
+        };
+        let tree = SyntaxTree::parse_with("| B  Fis |", &options);
+        let chords: Vec<String> = tree.chords().map(|c| c.unwrap().to_string()).collect();
+        assert_eq!(chords, ["Bb", "F#"]);
+    }
+
+    #[test]
+    fn error_span() {
+        let tree = SyntaxTree::parse("| C |\n|  Am W7 |");
+        let error = tree.chords().find_map(Result::err).unwrap();
+        assert_eq!(error.kind(), &ErrorKind::InvalidNatural('W'));
+        assert_eq!(error.span().line(), 1);
+        assert_eq!(error.span().chars(), 6..7);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    chart::Chart,
+    chord::Chord,
+    key::{Key, Mode},
+    note::Note,
+    quality::{Degree, Triad},
+};
+
+const DIATONIC_ROOT: f64 = 1.0;
+const DIATONIC_QUALITY: f64 = 1.0;
+const DIATONIC_BASS: f64 = 0.5;
+const TONIC_CHORD: f64 = 1.0;
+const FIRST_CHORD: f64 = 2.0;
+const LAST_CHORD: f64 = 3.0;
+
+/// Key that a chart may be in, with confidence from 0 to 1.
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyCandidate {
+    key: Key,
+    confidence: f64,
+}
+
+impl KeyCandidate {
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+pub(crate) fn detect_key(chart: &Chart) -> Vec<KeyCandidate> {
+    let chords: Vec<&Chord> = chart.chords().collect();
+    if chords.is_empty() {
+        return Vec::new();
+    }
+
+    let max_score = chords.len() as f64
+        * (DIATONIC_ROOT + DIATONIC_QUALITY + DIATONIC_BASS + TONIC_CHORD)
+        + FIRST_CHORD
+        + LAST_CHORD;
+
+    let mut candidates: Vec<KeyCandidate> = (0..12)
+        .flat_map(|tonic| [(tonic, Mode::Major), (tonic, Mode::Minor)])
+        .map(|(tonic, mode)| KeyCandidate {
+            key: spell_key(tonic, mode, &chords),
+            confidence: score(tonic, mode, &chords) / max_score,
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates
+}
+
+/// Triads built on a degree of the scale, by semitones from tonic.
+/// Minor keys also get harmonic minor dominant and leading-tone chords.
+fn diatonic_triads(mode: Mode, degree: i32) -> &'static [Triad] {
+    use Triad::*;
+
+    match (mode, degree) {
+        (Mode::Minor, 0) => &[Minor],
+        (Mode::Minor, 2) => &[Diminished],
+        (Mode::Minor, 3) => &[Major, Augmented],
+        (Mode::Minor, 5) => &[Minor],
+        (Mode::Minor, 7) => &[Minor, Major],
+        (Mode::Minor, 8) => &[Major],
+        (Mode::Minor, 10) => &[Major],
+        (Mode::Minor, 11) => &[Diminished],
+        (_, 0) => &[Major],
+        (_, 2) => &[Minor],
+        (_, 4) => &[Minor],
+        (_, 5) => &[Major],
+        (_, 7) => &[Major],
+        (_, 9) => &[Minor],
+        (_, 11) => &[Diminished],
+        _ => &[],
+    }
+}
+
+fn score(tonic: i32, mode: Mode, chords: &[&Chord]) -> f64 {
+    let degree = |note: &Note| (note.semitone() - tonic).rem_euclid(12);
+    let is_tonic_chord = |chord: &Chord| {
+        degree(chord.root()) == 0 && diatonic_triads(mode, 0).contains(&chord.quality().triad())
+    };
+
+    let mut score = 0.0;
+    for chord in chords {
+        let triads = diatonic_triads(mode, degree(chord.root()));
+        if !triads.is_empty() {
+            score += DIATONIC_ROOT;
+        }
+        // Suspended chords and power chords fit any major or minor triad
+        let quality = chord.quality();
+        let is_ambiguous =
+            quality.suspension().is_some() || quality.omitted().contains(&Degree::Third);
+        if triads.contains(&quality.triad())
+            || (is_ambiguous && triads.iter().any(|t| t != &Triad::Diminished))
+        {
+            score += DIATONIC_QUALITY;
+        }
+        let bass_note = chord.bass_note().unwrap_or(chord.root());
+        if !diatonic_triads(mode, degree(bass_note)).is_empty() {
+            score += DIATONIC_BASS;
+        }
+        if is_tonic_chord(chord) {
+            score += TONIC_CHORD;
+        }
+    }
+
+    if chords.first().is_some_and(|c| is_tonic_chord(c)) {
+        score += FIRST_CHORD;
+    }
+    if chords.last().is_some_and(|c| is_tonic_chord(c)) {
+        score += LAST_CHORD;
+    }
+    score
+}
+
+/// Takes tonic spelling from the chart if it's there,
+/// otherwise uses the one with fewer accidentals in key signature.
+fn spell_key(tonic: i32, mode: Mode, chords: &[&Chord]) -> Key {
+    let spelled = chords
+        .iter()
+        .map(|c| c.root())
+        .find(|n| n.semitone() == tonic)
+        .cloned();
+
+    match spelled {
+        Some(note) => Key::new(note, mode),
+        None => [true, false]
+            .map(|sharps| Key::new(Note::from_semitone(tonic, sharps), mode))
+            .into_iter()
+            .min_by_key(|k| k.fifths().abs())
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case("| C | Am | F | G | C |", "C")]
+    #[case("| Am | F | C | G | Am |", "Am")]
+    #[case("| Em | Am | H7 | Em |", "Em")]
+    #[case("| D | G | A7 | D |", "D")]
+    #[case("| Bb | Eb | F7 | Bb |", "Bb")]
+    #[case("| F#m | D | A | E |", "F#m")]
+    #[case("| Dsus4 | G/D | A5 | D |", "D")]
+    #[case("| A#m | D# | G# | A#m |", "A#m")]
+    fn detect_key(input: &str, output: &str) {
+        let candidates = Chart::from_str(input).unwrap().detect_key();
+        assert_eq!(candidates.len(), 24);
+        assert_eq!(candidates[0].key().to_string(), output);
+        assert!(candidates[0].confidence() <= 1.0);
+        assert!(candidates[0].confidence() > candidates[1].confidence());
+    }
+
+    #[test]
+    fn tonic_spelling() {
+        let candidates = Chart::from_str("| Eb | Ab | Bb |").unwrap().detect_key();
+        assert_eq!(candidates[0].key().to_string(), "Eb");
+        let candidates = Chart::from_str("| C | F | G |").unwrap().detect_key();
+        assert!(candidates.iter().any(|c| c.key().to_string() == "G#m"));
+        assert!(candidates.iter().any(|c| c.key().to_string() == "F#"));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Chart::from_str("").unwrap().detect_key(), Vec::new());
+    }
+}
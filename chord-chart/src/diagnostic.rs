@@ -0,0 +1,168 @@
+use crate::error::{Error, Span};
+use crate::locale::{warning_message, Locale};
+use std::{fmt, ops::Range};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WarningKind {
+    /// Text after a note that is dropped, "m" in "C/Dbm".
+    IgnoredAfterNote(String),
+    /// Slash without a bass note after it, "C/".
+    EmptyBassNote,
+    /// Line without bars that is left out of the chart.
+    EmptyLine,
+}
+
+impl WarningKind {
+    /// Code of the warning, W001 and on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::IgnoredAfterNote(_) => "W001",
+            Self::EmptyBassNote => "W002",
+            Self::EmptyLine => "W003",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        warning_message(self, locale)
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message(Locale::English))
+    }
+}
+
+/// Input that is read, but changes when the chart is written back.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Warning {
+    kind: WarningKind,
+    span: Span,
+}
+
+impl Warning {
+    /// Warning about `s[bytes]`.
+    pub(crate) fn new(kind: WarningKind, s: &str, bytes: Range<usize>) -> Self {
+        Self {
+            kind,
+            span: Span::new(s, bytes),
+        }
+    }
+
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}", self.kind, self.span)
+    }
+}
+
+/// Problem found while parsing a chart.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Diagnostic {
+    /// Input that can't be read and is left out of the chart.
+    Error(Error),
+    Warning(Warning),
+}
+
+impl Diagnostic {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::Error(error) => error.span(),
+            Self::Warning(warning) => &warning.span,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+
+    /// Code of the error or warning, "E002" or "W001".
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Error(error) => error.kind().code(),
+            Self::Warning(warning) => warning.kind.code(),
+        }
+    }
+
+    /// Message without the position, "invalid natural: W".
+    pub fn message(&self, locale: Locale) -> String {
+        match self {
+            Self::Error(error) => error.kind().message(locale),
+            Self::Warning(warning) => warning.kind.message(locale),
+        }
+    }
+
+    pub(crate) fn within(self, s: &str, part: &str) -> Self {
+        match self {
+            Self::Error(error) => Self::Error(error.within(s, part)),
+            Self::Warning(mut warning) => {
+                warning.span.within(s, part);
+                Self::Warning(warning)
+            }
+        }
+    }
+
+    pub(crate) fn on_line(self, line: usize) -> Self {
+        match self {
+            Self::Error(error) => Self::Error(error.on_line(line)),
+            Self::Warning(mut warning) => {
+                warning.span.on_line(line);
+                Self::Warning(warning)
+            }
+        }
+    }
+}
+
+/// Like rustc: "warning[W002]: empty bass note at line 1, column 4".
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = if self.is_error() { "error" } else { "warning" };
+        write!(
+            f,
+            "{severity}[{}]: {} at {}",
+            self.code(),
+            self.message(Locale::English),
+            self.span()
+        )
+    }
+}
+
+impl From<Error> for Diagnostic {
+    fn from(error: Error) -> Self {
+        Self::Error(error)
+    }
+}
+
+impl From<Warning> for Diagnostic {
+    fn from(warning: Warning) -> Self {
+        Self::Warning(warning)
+    }
+}
+
+/// Runs recovering `parse` and fails with the first error it found.
+pub(crate) fn first_error<T>(parse: impl FnOnce(&mut Vec<Diagnostic>) -> T) -> Result<T, Error> {
+    let mut diagnostics = Vec::new();
+    let value = parse(&mut diagnostics);
+    match diagnostics.into_iter().find(Diagnostic::is_error) {
+        Some(Diagnostic::Error(error)) => Err(error),
+        _ => Ok(value),
+    }
+}
+
+/// Moves diagnostics found in `part` to where `part` is in `s`.
+pub(crate) fn extend_within(
+    diagnostics: &mut Vec<Diagnostic>,
+    found: Vec<Diagnostic>,
+    s: &str,
+    part: &str,
+) {
+    diagnostics.extend(found.into_iter().map(|d| d.within(s, part)));
+}
@@ -1,8 +1,231 @@
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error {
+//! Errors of parsing. Errors and warnings have codes, "E002" or "W001", that
+//! stay the same when their messages change or are translated.
+
+use crate::locale::{error_message, Locale};
+use std::{fmt, ops::Range};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
     NoNatural,
     InvalidNatural(char),
     InvalidNote(&'static str),
+    InvalidMode(String),
+    NoDegree,
+    InvalidDegree(char),
+    InvalidOption(String),
+    InvalidQuality(String),
     BarLineShouldStartWithStripe(String),
     BarLineShouldEndWithStripe(String),
+    InvalidRepeatCount(String),
+    RepeatStartWithoutEnd,
+    RepeatEndWithoutStart,
+    VoltaOutsideRepeat,
+    DalSegnoWithoutSegno,
+    JumpWithoutCoda,
+    AlCodaWithoutToCoda,
+    AlFineWithoutFine,
+    InvalidTimeSignature(String),
+}
+
+impl ErrorKind {
+    /// Code of the error, E001 and on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoNatural => "E001",
+            Self::InvalidNatural(_) => "E002",
+            Self::InvalidNote(_) => "E003",
+            Self::InvalidMode(_) => "E004",
+            Self::NoDegree => "E005",
+            Self::InvalidDegree(_) => "E006",
+            Self::InvalidOption(_) => "E007",
+            Self::InvalidQuality(_) => "E008",
+            Self::BarLineShouldStartWithStripe(_) => "E009",
+            Self::BarLineShouldEndWithStripe(_) => "E010",
+            Self::InvalidRepeatCount(_) => "E011",
+            Self::RepeatStartWithoutEnd => "E012",
+            Self::RepeatEndWithoutStart => "E013",
+            Self::VoltaOutsideRepeat => "E014",
+            Self::DalSegnoWithoutSegno => "E015",
+            Self::JumpWithoutCoda => "E016",
+            Self::AlCodaWithoutToCoda => "E017",
+            Self::AlFineWithoutFine => "E018",
+            Self::InvalidTimeSignature(_) => "E019",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        error_message(self, locale)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message(Locale::English))
+    }
+}
+
+/// Where an error is: line of the chart, byte and char columns in that line
+/// and the text there. Everything counts from zero.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Span {
+    line: usize,
+    bytes: Range<usize>,
+    chars: Range<usize>,
+    token: String,
+}
+
+impl Span {
+    /// Span of `s[bytes]`.
+    pub(crate) fn new(s: &str, bytes: Range<usize>) -> Self {
+        let chars = s[..bytes.start].chars().count()..s[..bytes.end].chars().count();
+        let token = s[bytes.clone()].to_owned();
+        Self {
+            line: 0,
+            bytes,
+            chars,
+            token,
+        }
+    }
+
+    /// Moves a span found in `part` to where `part` is in `s`.
+    pub(crate) fn within(&mut self, s: &str, part: &str) {
+        let bytes = offset_in(s, part);
+        let chars = s[..bytes].chars().count();
+        self.bytes = self.bytes.start + bytes..self.bytes.end + bytes;
+        self.chars = self.chars.start + chars..self.chars.end + chars;
+    }
+
+    pub(crate) fn on_line(&mut self, line: usize) {
+        self.line = line;
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn bytes(&self) -> Range<usize> {
+        self.bytes.clone()
+    }
+
+    pub fn chars(&self) -> Range<usize> {
+        self.chars.clone()
+    }
+
+    /// Offending text, empty if something is missing.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// Byte where `part` starts in `s`, which it has to be a slice of: parsers
+/// hand down parts of the text they split, never copies.
+pub(crate) fn offset_in(s: &str, part: &str) -> usize {
+    let start = (part.as_ptr() as usize).wrapping_sub(s.as_ptr() as usize);
+    assert!(
+        start <= s.len() && part.len() <= s.len() - start,
+        "part should be a slice of the text it is in"
+    );
+    start
+}
+
+/// Counts from one, as editors do: "line 2, column 8".
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line + 1, self.chars.start + 1)
+    }
+}
+
+/// What went wrong and where. It used to be an enum of what `kind` returns now,
+/// so code that matched on it matches on `error.kind()`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    kind: ErrorKind,
+    span: Span,
+}
+
+impl Error {
+    /// Error about `s[bytes]`.
+    pub(crate) fn new(kind: ErrorKind, s: &str, bytes: Range<usize>) -> Self {
+        Self {
+            kind,
+            span: Span::new(s, bytes),
+        }
+    }
+
+    /// Error about the whole of `s` without surrounding whitespace.
+    pub(crate) fn trimmed(kind: ErrorKind, s: &str) -> Self {
+        let start = s.len() - s.trim_start().len();
+        Self::new(kind, s, start..s.trim_end().len().max(start))
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Moves the span of an error found in `part` to where `part` is in `s`.
+    pub(crate) fn within(mut self, s: &str, part: &str) -> Self {
+        self.span.within(s, part);
+        self
+    }
+
+    pub(crate) fn on_line(mut self, line: usize) -> Self {
+        self.span.on_line(line);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}", self.kind, self.span)
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Chart;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case(ErrorKind::NoNatural, "E001", "no natural")]
+    #[case(ErrorKind::InvalidNatural('W'), "E002", "invalid natural: W")]
+    #[case(ErrorKind::InvalidQuality("majj7".into()), "E008", "invalid quality: majj7")]
+    #[case(
+        ErrorKind::BarLineShouldEndWithStripe("| C".into()),
+        "E010",
+        "bar line should end with stripe: | C"
+    )]
+    #[case(ErrorKind::RepeatEndWithoutStart, "E013", "repeat end without start")]
+    #[case(ErrorKind::AlFineWithoutFine, "E018", "al Fine without Fine")]
+    #[case(ErrorKind::InvalidTimeSignature("3/5".into()), "E019", "invalid time signature: 3/5")]
+    fn kind(kind: ErrorKind, code: &str, message: &str) {
+        assert_eq!(kind.code(), code);
+        assert_eq!(kind.to_string(), message);
+    }
+
+    #[test]
+    fn display() {
+        let error = Chart::from_str("| C |\n| Am | W7 |").unwrap_err();
+        assert_eq!(error.to_string(), "invalid natural: W at line 2, column 8");
+    }
+
+    #[test]
+    fn offset() {
+        let s = "| Am | W7 |";
+        assert_eq!(offset_in(s, &s[7..9]), 7);
+        assert_eq!(offset_in(s, &s[11..]), 11);
+    }
+
+    #[test]
+    #[should_panic(expected = "part should be a slice")]
+    fn offset_of_copy() {
+        let s = "| Am | W7 |";
+        offset_in(s, &String::from("W7"));
+    }
 }
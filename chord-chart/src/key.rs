@@ -0,0 +1,260 @@
+use crate::{
+    error::{Error, ErrorKind},
+    note::Note,
+    parse::ParseOptions,
+    transpose::Scale,
+};
+use std::{fmt, str::FromStr};
+
+/// Semitones above tonic for each degree of major scale.
+const MAJOR_SEMITONES: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+}
+
+impl Mode {
+    /// Degree of major scale the mode starts from.
+    fn rotation(&self) -> usize {
+        match self {
+            Self::Major => 0,
+            Self::Dorian => 1,
+            Self::Phrygian => 2,
+            Self::Lydian => 3,
+            Self::Mixolydian => 4,
+            Self::Minor => 5,
+            Self::Locrian => 6,
+        }
+    }
+
+    /// Letter steps and semitones from tonic of the mode to tonic of its relative major.
+    fn relative_major(&self) -> (i32, i32) {
+        let rotation = self.rotation();
+        (-(rotation as i32), -MAJOR_SEMITONES[rotation])
+    }
+
+    /// Semitones above tonic for each degree of the mode.
+    pub(crate) fn semitones(&self) -> [i32; 7] {
+        let rotation = self.rotation();
+        let mut semitones = [0; 7];
+        for (degree, value) in semitones.iter_mut().enumerate() {
+            *value = (MAJOR_SEMITONES[(degree + rotation) % 7] - MAJOR_SEMITONES[rotation])
+                .rem_euclid(12);
+        }
+        semitones
+    }
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        match trimmed {
+            "" | "M" => return Ok(Self::Major),
+            "m" | "-" => return Ok(Self::Minor),
+            _ => (),
+        }
+
+        Ok(match trimmed.to_lowercase().as_str() {
+            "maj" | "major" | "ionian" => Self::Major,
+            "min" | "minor" | "aeolian" => Self::Minor,
+            "dorian" => Self::Dorian,
+            "phrygian" => Self::Phrygian,
+            "lydian" => Self::Lydian,
+            "mixolydian" => Self::Mixolydian,
+            "locrian" => Self::Locrian,
+            _ => return Err(Error::trimmed(ErrorKind::InvalidMode(trimmed.into()), s)),
+        })
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Major => "",
+            Self::Minor => "m",
+            Self::Dorian => " dorian",
+            Self::Phrygian => " phrygian",
+            Self::Lydian => " lydian",
+            Self::Mixolydian => " mixolydian",
+            Self::Locrian => " locrian",
+        })
+    }
+}
+
+/// Tonic with a mode, like "F#m" or "Eb dorian".
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Key {
+    tonic: Note,
+    mode: Mode,
+}
+
+impl Key {
+    pub fn new(tonic: Note, mode: Mode) -> Self {
+        Self { tonic, mode }
+    }
+
+    pub fn tonic(&self) -> &Note {
+        &self.tonic
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Key signature of the key, taken from its relative major.
+    pub fn scale(&self) -> Scale {
+        Scale::major(&self.relative_major())
+    }
+
+    /// Number of sharps in key signature, or flats if negative.
+    pub(crate) fn fifths(&self) -> i32 {
+        self.relative_major().fifths()
+    }
+
+    /// Degree of `note` in major scale of the tonic counting from zero,
+    /// and semitones it is altered by: Bb in C is (6, -1).
+    pub(crate) fn degree_of(&self, note: &Note) -> (i32, i32) {
+        self.degree_in(note, &MAJOR_SEMITONES)
+    }
+
+    /// Like `degree_of`, but in the scale of the key mode: C in A minor is (2, 0).
+    pub(crate) fn mode_degree_of(&self, note: &Note) -> (i32, i32) {
+        self.degree_in(note, &self.mode.semitones())
+    }
+
+    /// Degree comes from pitch, so F# in Gb is the tonic. Notes out of the scale keep
+    /// their letter, G# in C is #5 and Ab is b6, unless it is further than a semitone
+    /// off: then they are the next degree up, flattened.
+    fn degree_in(&self, note: &Note, scale: &[i32; 7]) -> (i32, i32) {
+        let semitones = (note.semitone() - self.tonic.semitone()).rem_euclid(12);
+        if let Some(degree) = scale.iter().position(|&s| s == semitones) {
+            return (degree as i32, 0);
+        }
+        let offset = |degree: i32| (semitones - scale[degree as usize] + 6).rem_euclid(12) - 6;
+        let letter = self.tonic.steps_to(note);
+        let degree = if offset(letter).abs() <= 1 {
+            letter
+        } else {
+            scale.iter().position(|&s| s > semitones).unwrap_or(0) as i32
+        };
+        (degree, offset(degree))
+    }
+
+    /// Reverse of `degree_of`.
+    pub(crate) fn note_at(&self, degree: i32, offset: i32) -> Note {
+        self.tonic
+            .add_interval(degree, MAJOR_SEMITONES[degree as usize] + offset)
+    }
+
+    /// Parses key with tonic written in the dialect of `options`, "Fis" or "Es dorian" in German.
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        let trimmed = s.trim();
+        let (tonic, mode) =
+            Note::parse_prefix_with(trimmed, options).map_err(|e| e.within(s, trimmed))?;
+        let mode = Mode::from_str(mode).map_err(|e| e.within(s, mode))?;
+        Ok(Self::new(tonic, mode))
+    }
+
+    pub(crate) fn relative_major(&self) -> Note {
+        let (steps, semitones) = self.mode.relative_major();
+        self.tonic.add_interval(steps, semitones)
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_with(s, &ParseOptions::default())
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.tonic, self.mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mode::*;
+    use super::*;
+    use crate::{parse::Dialect, transpose::Transpose};
+    use test_case::case;
+
+    #[case("C", Major, "C")]
+    #[case("F#m", Minor, "F#m")]
+    #[case("Eb dorian", Dorian, "Eb dorian")]
+    #[case(" a Minor ", Minor, "Am")]
+    #[case("Gmaj", Major, "G")]
+    #[case("Dbm", Minor, "Dbm")]
+    #[case("E aeolian", Minor, "Em")]
+    #[case("F Lydian", Lydian, "F lydian")]
+    fn basics_ok(input: &str, mode: Mode, output: &str) {
+        let value = Key::from_str(input).unwrap();
+        assert_eq!(value.mode(), mode);
+        assert_eq!(value.to_string(), output);
+    }
+
+    #[case("", ErrorKind::NoNatural)]
+    #[case("W", ErrorKind::InvalidNatural('W'))]
+    #[case("C blues", ErrorKind::InvalidMode("blues".into()))]
+    #[case("Cm7", ErrorKind::InvalidMode("m7".into()))]
+    fn basics_err(input: &str, error: ErrorKind) {
+        assert_eq!(Key::from_str(input).unwrap_err().kind(), &error);
+    }
+
+    #[case(" C blues", 3..8, "blues")]
+    #[case("W dorian", 0..1, "W")]
+    fn error_span(input: &str, chars: std::ops::Range<usize>, token: &str) {
+        let error = Key::from_str(input).unwrap_err();
+        assert_eq!(error.span().chars(), chars);
+        assert_eq!(error.span().token(), token);
+    }
+
+    #[case("Fis", Dialect::German, "F#")]
+    #[case("Es dorian", Dialect::German, "Eb dorian")]
+    #[case("Bm", Dialect::German, "Bbm")]
+    #[case("Bm", Dialect::Dutch, "Hm")]
+    fn dialect(input: &str, dialect: Dialect, output: &str) {
+        let key = Key::from_str_with(
+            input,
+            &ParseOptions {
+                dialect,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(key.to_string(), output);
+    }
+
+    #[case("Dm", 10, "Bb")]
+    #[case("Ebm", 11, "Cb")]
+    #[case("A dorian", 6, "F#")]
+    #[case("F# phrygian", 1, "C#")]
+    #[case("G mixolydian", 5, "F")]
+    #[case("H locrian", 5, "F")]
+    #[case("G#m", 10, "A#")]
+    fn scale(key: &str, semitone_incr: i32, output: &str) {
+        let scale = Key::from_str(key).unwrap().scale();
+        let note = Note::from_str("C").unwrap();
+        assert_eq!(note.transpose(&semitone_incr, &scale).to_string(), output);
+    }
+
+    #[case(Major, [0, 2, 4, 5, 7, 9, 11])]
+    #[case(Minor, [0, 2, 3, 5, 7, 8, 10])]
+    #[case(Dorian, [0, 2, 3, 5, 7, 9, 10])]
+    #[case(Locrian, [0, 1, 3, 5, 6, 8, 10])]
+    fn mode_semitones(mode: Mode, semitones: [i32; 7]) {
+        assert_eq!(mode.semitones(), semitones);
+    }
+}
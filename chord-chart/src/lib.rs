@@ -3,11 +3,42 @@ mod bar;
 mod bar_line;
 mod chart;
 mod chord;
+mod cst;
+mod detection;
+mod diagnostic;
 mod error;
+mod key;
+mod locale;
+mod nashville;
+mod navigation;
 mod note;
+mod parse;
+mod quality;
+mod render;
+mod roman;
+mod section;
+mod solfege;
+mod time;
 mod transpose;
+mod unroll;
+mod volta;
 
+pub use bar::Bar;
 pub use chart::Chart;
-pub use error::Error;
+pub use chord::Chord;
+pub use cst::{SyntaxTree, Token, TokenKind};
+pub use detection::KeyCandidate;
+pub use diagnostic::{Diagnostic, Warning, WarningKind};
+pub use error::{Error, ErrorKind, Span};
+pub use key::{Key, Mode};
+pub use locale::Locale;
+pub use navigation::{Marker, Until};
 pub use note::Note;
+pub use parse::{Dialect, ParseOptions};
+pub use quality::{Alteration, ChordQuality, Degree, Extension, Seventh, Suspension, Triad};
+pub use render::{Accidentals, NoteNaming, RenderOptions, Spacing};
+pub use roman::RomanNumeral;
+pub use section::Section;
+pub use time::TimeSignature;
 pub use transpose::Transpose;
+pub use unroll::PlayedBar;
@@ -0,0 +1,161 @@
+use crate::{
+    diagnostic::WarningKind,
+    error::{Error, ErrorKind},
+};
+use std::str::FromStr;
+
+/// Language of error and warning messages.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    Russian,
+    German,
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "en" | "english" => Ok(Self::English),
+            "ru" | "russian" => Ok(Self::Russian),
+            "de" | "german" => Ok(Self::German),
+            _ => Err(Error::trimmed(ErrorKind::InvalidOption(s.trim().into()), s)),
+        }
+    }
+}
+
+pub(crate) fn error_message(kind: &ErrorKind, locale: Locale) -> String {
+    use self::ErrorKind::*;
+    use self::Locale::*;
+
+    match (kind, locale) {
+        (NoNatural, English) => "no natural".into(),
+        (NoNatural, Russian) => "нет названия ноты".into(),
+        (NoNatural, German) => "Notenname fehlt".into(),
+        (InvalidNatural(natural), English) => format!("invalid natural: {natural}"),
+        (InvalidNatural(natural), Russian) => format!("неверное название ноты: {natural}"),
+        (InvalidNatural(natural), German) => format!("ungültiger Notenname: {natural}"),
+        (InvalidNote(note), English) => format!("invalid note: {note}"),
+        (InvalidNote(note), Russian) => format!("неверная нота: {note}"),
+        (InvalidNote(note), German) => format!("ungültige Note: {note}"),
+        (InvalidMode(mode), English) => format!("invalid mode: {mode}"),
+        (InvalidMode(mode), Russian) => format!("неверный лад: {mode}"),
+        (InvalidMode(mode), German) => format!("ungültiger Modus: {mode}"),
+        (NoDegree, English) => "no degree".into(),
+        (NoDegree, Russian) => "нет ступени".into(),
+        (NoDegree, German) => "Stufe fehlt".into(),
+        (InvalidDegree(degree), English) => format!("invalid degree: {degree}"),
+        (InvalidDegree(degree), Russian) => format!("неверная ступень: {degree}"),
+        (InvalidDegree(degree), German) => format!("ungültige Stufe: {degree}"),
+        (InvalidOption(option), English) => format!("invalid option: {option}"),
+        (InvalidOption(option), Russian) => format!("неверный параметр: {option}"),
+        (InvalidOption(option), German) => format!("ungültige Option: {option}"),
+        (InvalidQuality(quality), English) => format!("invalid quality: {quality}"),
+        (InvalidQuality(quality), Russian) => format!("неверное обозначение аккорда: {quality}"),
+        (InvalidQuality(quality), German) => format!("ungültiges Akkordsymbol: {quality}"),
+        (BarLineShouldStartWithStripe(line), English) => {
+            format!("bar line should start with stripe: {line}")
+        }
+        (BarLineShouldStartWithStripe(line), Russian) => {
+            format!("строка тактов должна начинаться с черты: {line}")
+        }
+        (BarLineShouldStartWithStripe(line), German) => {
+            format!("Taktzeile muss mit Taktstrich beginnen: {line}")
+        }
+        (BarLineShouldEndWithStripe(line), English) => {
+            format!("bar line should end with stripe: {line}")
+        }
+        (BarLineShouldEndWithStripe(line), Russian) => {
+            format!("строка тактов должна заканчиваться чертой: {line}")
+        }
+        (BarLineShouldEndWithStripe(line), German) => {
+            format!("Taktzeile muss mit Taktstrich enden: {line}")
+        }
+        (InvalidRepeatCount(count), English) => format!("invalid repeat count: {count}"),
+        (InvalidRepeatCount(count), Russian) => format!("неверное число повторов: {count}"),
+        (InvalidRepeatCount(count), German) => {
+            format!("ungültige Anzahl der Wiederholungen: {count}")
+        }
+        (RepeatStartWithoutEnd, English) => "repeat start without end".into(),
+        (RepeatStartWithoutEnd, Russian) => "начало повтора без конца".into(),
+        (RepeatStartWithoutEnd, German) => "Wiederholungsbeginn ohne Ende".into(),
+        (RepeatEndWithoutStart, English) => "repeat end without start".into(),
+        (RepeatEndWithoutStart, Russian) => "конец повтора без начала".into(),
+        (RepeatEndWithoutStart, German) => "Wiederholungsende ohne Beginn".into(),
+        (VoltaOutsideRepeat, English) => "volta outside repeat".into(),
+        (VoltaOutsideRepeat, Russian) => "вольта вне повтора".into(),
+        (VoltaOutsideRepeat, German) => "Volta außerhalb einer Wiederholung".into(),
+        (DalSegnoWithoutSegno, English) => "D.S. without segno".into(),
+        (DalSegnoWithoutSegno, Russian) => "D.S. без знака сеньо".into(),
+        (DalSegnoWithoutSegno, German) => "D.S. ohne Segno".into(),
+        (JumpWithoutCoda, English) => "jump to coda without coda".into(),
+        (JumpWithoutCoda, Russian) => "переход к коде без коды".into(),
+        (JumpWithoutCoda, German) => "Sprung zur Coda ohne Coda".into(),
+        (AlCodaWithoutToCoda, English) => "al Coda without To Coda".into(),
+        (AlCodaWithoutToCoda, Russian) => "al Coda без To Coda".into(),
+        (AlCodaWithoutToCoda, German) => "al Coda ohne To Coda".into(),
+        (AlFineWithoutFine, English) => "al Fine without Fine".into(),
+        (AlFineWithoutFine, Russian) => "al Fine без Fine".into(),
+        (AlFineWithoutFine, German) => "al Fine ohne Fine".into(),
+        (InvalidTimeSignature(time), English) => format!("invalid time signature: {time}"),
+        (InvalidTimeSignature(time), Russian) => format!("неверный размер такта: {time}"),
+        (InvalidTimeSignature(time), German) => format!("ungültige Taktart: {time}"),
+    }
+}
+
+pub(crate) fn warning_message(kind: &WarningKind, locale: Locale) -> String {
+    use self::Locale::*;
+    use self::WarningKind::*;
+
+    match (kind, locale) {
+        (IgnoredAfterNote(rest), English) => format!("ignored after note: {rest}"),
+        (IgnoredAfterNote(rest), Russian) => format!("пропущено после ноты: {rest}"),
+        (IgnoredAfterNote(rest), German) => format!("nach der Note ignoriert: {rest}"),
+        (EmptyBassNote, English) => "empty bass note".into(),
+        (EmptyBassNote, Russian) => "не указан бас".into(),
+        (EmptyBassNote, German) => "Bassnote fehlt".into(),
+        (EmptyLine, English) => "empty line".into(),
+        (EmptyLine, Russian) => "пустая строка".into(),
+        (EmptyLine, German) => "leere Zeile".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Chart;
+    use test_case::case;
+
+    #[case("en", Locale::English)]
+    #[case(" RU", Locale::Russian)]
+    #[case("german", Locale::German)]
+    fn locale_ok(input: &str, output: Locale) {
+        assert_eq!(Locale::from_str(input).unwrap(), output);
+    }
+
+    #[test]
+    fn locale_err() {
+        assert_eq!(
+            Locale::from_str("fr").unwrap_err().kind(),
+            &ErrorKind::InvalidOption("fr".into())
+        );
+    }
+
+    #[case(Locale::English, "invalid natural: W")]
+    #[case(Locale::Russian, "неверное название ноты: W")]
+    #[case(Locale::German, "ungültiger Notenname: W")]
+    fn error(locale: Locale, message: &str) {
+        let error = Chart::from_str("| C | W |").unwrap_err();
+        assert_eq!(error.kind().message(locale), message);
+    }
+
+    #[case(Locale::English, "empty bass note")]
+    #[case(Locale::Russian, "не указан бас")]
+    #[case(Locale::German, "Bassnote fehlt")]
+    fn warning(locale: Locale, message: &str) {
+        let (_, diagnostics) = Chart::from_str_recovering("| C/ |", &Default::default());
+        assert_eq!(diagnostics[0].message(locale), message);
+    }
+}
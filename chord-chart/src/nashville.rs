@@ -0,0 +1,155 @@
+use crate::{
+    chart::Chart,
+    chord::Chord,
+    error::{Error, ErrorKind},
+    key::Key,
+    note::Note,
+    render::Spacing,
+};
+
+pub(crate) fn to_nashville(chart: &Chart, key: &Key) -> String {
+    chart.format_with(Spacing::Normal, &|c| {
+        let symbols = c.symbols();
+        let wrap =
+            symbols.starts_with(|c: char| c.is_ascii_digit() || c == '(') || symbols.contains('/');
+        let chord = if wrap {
+            c.with_symbols(&format!("({symbols})"))
+        } else {
+            c.with_symbols(symbols)
+        };
+        chord.format_with(&|n| format_number(n, key))
+    })
+}
+
+pub(crate) fn from_nashville(s: &str, key: &Key) -> Result<Chart, Error> {
+    let chart = Chart::parse_with(s, &|c, d| {
+        Chord::parse_into(c, &|n| parse_number(n, key), d)
+    })?;
+    Ok(chart.map(&|c| c.with_symbols(unwrap(c.symbols()))))
+}
+
+/// Symbols without the parentheses around all of them, if there are.
+fn unwrap(symbols: &str) -> &str {
+    let inner = match symbols.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => return symbols,
+    };
+    // "(7)(b9)" is two groups, not one around all of it
+    let mut depth = 0;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return symbols,
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    inner
+}
+
+fn format_number(note: &Note, key: &Key) -> String {
+    let (degree, offset) = key.degree_of(note);
+    let accidental = if offset < 0 {
+        "b".repeat(offset.unsigned_abs() as usize)
+    } else {
+        "#".repeat(offset as usize)
+    };
+    accidental + &(degree + 1).to_string()
+}
+
+fn parse_number<'a>(s: &'a str, key: &Key) -> Result<(Note, &'a str), Error> {
+    let flats = s.len() - s.trim_start_matches('b').len();
+    let sharps = s.len() - s.trim_start_matches('#').len();
+    let offset = sharps as i32 - flats as i32;
+
+    let mut chars = s[flats + sharps..].chars();
+    match chars.next() {
+        Some(c @ '1'..='7') => {
+            let degree = c as i32 - '1' as i32;
+            Ok((key.note_at(degree, offset), chars.as_str()))
+        }
+        Some(c) => Err(Error::new(
+            ErrorKind::InvalidDegree(c),
+            s,
+            flats + sharps..flats + sharps + c.len_utf8(),
+        )),
+        None => Err(Error::new(ErrorKind::NoDegree, s, s.len()..s.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case("| C F | Am G |", "C", "| 1 4 | 6m 5 |")]
+    #[case("| C/E | Bb | Ab | G7sus4 |", "C", "| 1/3 | b7 | b6 | 5(7sus4) |")]
+    #[case("| F#m7b5 | Dmaj7/F# |", "D", "| 3m7b5 | 1maj7/3 |")]
+    #[case("| C6/9 | F6/9/A |", "C", "| 1(6/9) | 4(6/9)/6 |")]
+    #[case("| D | F#7 | Hm |", "D", "| 1 | 3(7) | 6m |")]
+    #[case("| Am | C | E7 | G |", "Am", "| 1m | b3 | 5(7) | b7 |")]
+    #[case("| Gb | Cb | Db |", "Gb", "| 1 | 4 | 5 |")]
+    #[case("| C# | E# |", "C#", "| 1 | 3 |")]
+    #[case("| G# |", "C", "| #5 |")]
+    #[case("| F# C# |", "Gb", "| 1 5 |")]
+    #[case("| Gb Db |", "F#", "| 1 5 |")]
+    #[case("| Cb | E# |", "C", "| 7 | 4 |")]
+    #[case("| Abb | Fb |", "C", "| 5 | 3 |")]
+    #[case("| E## |", "C", "| b5 |")]
+    #[case("3/4 | C | 6/8 G |", "C", "3/4 | 1 | 6/8 5 |")]
+    #[case(
+        "| C(b9) | Gm(maj7) | C(7)(b9) |",
+        "C",
+        "| 1((b9)) | 5m(maj7) | 1((7)(b9)) |"
+    )]
+    fn to_nashville(input: &str, key: &str, output: &str) {
+        let key = Key::from_str(key).unwrap();
+        assert_eq!(Chart::from_str(input).unwrap().to_nashville(&key), output);
+    }
+
+    #[case("| 1 4 | 6m 5 |", "C", "| C F | Am G |")]
+    #[case("|1/3 b7| b6 57sus4|", "C", "| C/E Bb | Ab G7sus4 |")]
+    #[case("| 1 | 4 | 5 |", "Eb", "| Eb | Ab | Bb |")]
+    #[case("| 1 | 4 | 5 |", "F#", "| F# | H | C# |")]
+    #[case("| 1m | b3 | 57 | b7 |", "Am", "| Am | C | E7 | G |")]
+    #[case("| #4dim7 | 5/7 | 1/ |", "G", "| C#dim7 | D/F# | G |")]
+    #[case("| 16/9 | 46/9/6 |", "C", "| C6/9 | F6/9/A |")]
+    #[case("| 5(7sus4) | 1(6/9) | 5(7/6)/7 |", "C", "| G7sus4 | C6/9 | G7/6/H |")]
+    #[case("| 1(7)(b9) | 1m(maj7) |", "C", "| C(7)(b9) | Cm(maj7) |")]
+    #[case("| 5/4 1 | 1 5/4 |", "C", "| G/F C | C G/F |")]
+    #[case("4/4 | 1 | 6/8 5 |", "C", "4/4 | C | 6/8 G |")]
+    fn from_nashville(input: &str, key: &str, output: &str) {
+        let key = Key::from_str(key).unwrap();
+        assert_eq!(
+            Chart::from_nashville(input, &key).unwrap().to_string(),
+            output
+        );
+    }
+
+    #[case("| F/C | G/D |", "C")]
+    #[case("| C/E | Dm/F | G/H |", "C")]
+    #[case("|: Am/C | 6/8 E7/H :|", "Am")]
+    #[case("| G7sus4 | C6/9 | Fadd9 | G13 |", "C")]
+    #[case("| C7/6/E | F6/9/A | C(7) | C(b9) | Gm(maj7) |", "C")]
+    fn round_trip(input: &str, key: &str) {
+        let key = Key::from_str(key).unwrap();
+        let numbers = Chart::from_str(input).unwrap().to_nashville(&key);
+        assert_eq!(
+            Chart::from_nashville(&numbers, &key).unwrap().to_string(),
+            input
+        );
+    }
+
+    #[case("| 8 |", ErrorKind::InvalidDegree('8'))]
+    #[case("| C |", ErrorKind::InvalidDegree('C'))]
+    #[case("| b |", ErrorKind::NoDegree)]
+    #[case("| 1/x |", ErrorKind::InvalidDegree('x'))]
+    fn from_nashville_err(input: &str, error: ErrorKind) {
+        let key = Key::from_str("C").unwrap();
+        assert_eq!(
+            Chart::from_nashville(input, &key).unwrap_err().kind(),
+            &error
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::{fmt, ops::Range};
+
+use crate::{
+    diagnostic::Diagnostic,
+    error::{Error, ErrorKind},
+};
+
+/// Where D.C. and D.S. play to after going back.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Until {
+    /// To the end of the chart.
+    End,
+    /// To "Fine".
+    Fine,
+    /// To "To Coda", then on from "Coda".
+    Coda,
+}
+
+/// Navigation marker on a bar. Segno and Coda mark where the bar starts,
+/// the others apply after the bar is played.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Marker {
+    Segno,
+    Coda,
+    ToCoda,
+    Fine,
+    /// "D.C.", back to the start of the chart.
+    DaCapo(Until),
+    /// "D.S.", back to the segno.
+    DalSegno(Until),
+}
+
+/// Written forms, longest first. Letters may be in any case.
+const MARKERS: [(&str, Marker); 12] = [
+    ("D.C. al Fine", Marker::DaCapo(Until::Fine)),
+    ("D.C. al Coda", Marker::DaCapo(Until::Coda)),
+    ("D.S. al Fine", Marker::DalSegno(Until::Fine)),
+    ("D.S. al Coda", Marker::DalSegno(Until::Coda)),
+    ("D.C.", Marker::DaCapo(Until::End)),
+    ("D.S.", Marker::DalSegno(Until::End)),
+    ("To Coda", Marker::ToCoda),
+    ("Segno", Marker::Segno),
+    ("Coda", Marker::Coda),
+    ("Fine", Marker::Fine),
+    ("𝄋", Marker::Segno),
+    ("𝄌", Marker::Coda),
+];
+
+impl Marker {
+    /// Reads a marker at the start of `s`, returning the rest of it.
+    /// Words of the marker can be split by any whitespace.
+    pub(crate) fn parse_prefix(s: &str) -> Option<(Self, &str)> {
+        MARKERS.iter().find_map(|&(text, marker)| {
+            let mut rest = s;
+            for (index, word) in text.split(' ').enumerate() {
+                if index > 0 {
+                    let trimmed = rest.trim_start();
+                    if trimmed.len() == rest.len() {
+                        return None;
+                    }
+                    rest = trimmed;
+                }
+                if !rest.get(..word.len())?.eq_ignore_ascii_case(word) {
+                    return None;
+                }
+                rest = &rest[word.len()..];
+            }
+            match rest.chars().next() {
+                Some(c) if !c.is_whitespace() => None,
+                _ => Some((marker, rest)),
+            }
+        })
+    }
+
+    /// Segno and Coda are written before chords of the bar, the others after.
+    pub fn is_start(self) -> bool {
+        matches!(self, Self::Segno | Self::Coda)
+    }
+
+    /// Markers the jump needs, with errors to report when they are missing.
+    fn requires(self) -> Vec<(Self, ErrorKind)> {
+        let mut vec = Vec::new();
+        let until = match self {
+            Self::DaCapo(until) => until,
+            Self::DalSegno(until) => {
+                vec.push((Self::Segno, ErrorKind::DalSegnoWithoutSegno));
+                until
+            }
+            Self::ToCoda => return vec![(Self::Coda, ErrorKind::JumpWithoutCoda)],
+            Self::Segno | Self::Coda | Self::Fine => return vec,
+        };
+        match until {
+            Until::End => {}
+            Until::Fine => vec.push((Self::Fine, ErrorKind::AlFineWithoutFine)),
+            Until::Coda => vec.extend([
+                (Self::ToCoda, ErrorKind::AlCodaWithoutToCoda),
+                (Self::Coda, ErrorKind::JumpWithoutCoda),
+            ]),
+        }
+        vec
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = MARKERS
+            .iter()
+            .find(|(_, marker)| marker == self)
+            .map(|(text, _)| text)
+            .expect("every marker should have a written form");
+        f.write_str(text)
+    }
+}
+
+/// Repeat signs, endings and markers that are checked against each other
+/// across bar lines, as errors to report if they are left unmatched.
+#[derive(Debug, Default)]
+pub(crate) struct Signs {
+    /// "|:", ":|" and endings in order.
+    repeats: Vec<Error>,
+    /// Jumps with markers they need.
+    jumps: Vec<(Marker, Error)>,
+    markers: Vec<Marker>,
+}
+
+impl Signs {
+    /// Notes a repeat sign or an ending.
+    pub(crate) fn repeat(&mut self, error: Error) {
+        self.repeats.push(error);
+    }
+
+    /// Notes `marker` written at `s[bytes]`.
+    pub(crate) fn marker(&mut self, marker: Marker, s: &str, bytes: Range<usize>) {
+        for (required, kind) in marker.requires() {
+            self.jumps
+                .push((required, Error::new(kind, s, bytes.clone())));
+        }
+        self.markers.push(marker);
+    }
+
+    pub(crate) fn append(&mut self, other: Self) {
+        self.repeats.extend(other.repeats);
+        self.jumps.extend(other.jumps);
+        self.markers.extend(other.markers);
+    }
+
+    /// Moves signs found in `part` to where `part` is in `s`.
+    pub(crate) fn within(self, s: &str, part: &str) -> Self {
+        self.map(|e| e.within(s, part))
+    }
+
+    pub(crate) fn on_line(self, line: usize) -> Self {
+        self.map(|e| e.on_line(line))
+    }
+
+    pub(crate) fn check(self, diagnostics: &mut Vec<Diagnostic>) {
+        match_repeats(self.repeats, diagnostics);
+        for (required, error) in self.jumps {
+            if !self.markers.contains(&required) {
+                diagnostics.push(error.into());
+            }
+        }
+    }
+
+    fn map(self, f: impl Fn(Error) -> Error) -> Self {
+        Self {
+            repeats: self.repeats.into_iter().map(&f).collect(),
+            jumps: self.jumps.into_iter().map(|(m, e)| (m, f(e))).collect(),
+            markers: self.markers,
+        }
+    }
+}
+
+/// Reports "|:" without ":|", ":|" without "|:" and endings outside of repeats.
+/// Repeats don't nest, so "|:" is closed by the first ":|" after it. Endings after
+/// the first one follow that ":|" and can end with ":|" too.
+fn match_repeats(repeats: Vec<Error>, diagnostics: &mut Vec<Diagnostic>) {
+    let mut open = None;
+    // The open repeat, or the one closed last, has a first ending
+    let mut endings = false;
+    // In an ending after the repeat is closed. Unless it ends with ":|", it's the last one
+    let mut later_ending = false;
+    for sign in repeats {
+        match sign.kind() {
+            ErrorKind::RepeatStartWithoutEnd => {
+                diagnostics.extend(open.replace(sign).map(Diagnostic::from));
+                endings = false;
+                later_ending = false;
+            }
+            ErrorKind::RepeatEndWithoutStart => {
+                if open.take().is_none() && !later_ending {
+                    diagnostics.push(sign.into());
+                }
+                later_ending = false;
+            }
+            _ if open.is_some() => endings = true,
+            _ if endings && !later_ending => later_ending = true,
+            _ => diagnostics.push(sign.into()),
+        }
+    }
+    diagnostics.extend(open.map(Diagnostic::from));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("Segno C", Marker::Segno, " C")]
+    #[case("𝄌 C", Marker::Coda, " C")]
+    #[case("fine", Marker::Fine, "")]
+    #[case("To  Coda", Marker::ToCoda, "")]
+    #[case("D.S. al Coda G", Marker::DalSegno(Until::Coda), " G")]
+    #[case("d.c.", Marker::DaCapo(Until::End), "")]
+    #[case("D.C. al", Marker::DaCapo(Until::End), " al")]
+    fn marker(input: &str, marker: Marker, rest: &str) {
+        assert_eq!(Marker::parse_prefix(input), Some((marker, rest)));
+    }
+
+    #[case("C")]
+    #[case("Codas")]
+    #[case("To")]
+    #[case("D.S.al Coda")]
+    #[case("Fine7")]
+    fn not_marker(input: &str) {
+        assert_eq!(Marker::parse_prefix(input), None);
+    }
+
+    #[case(Marker::DalSegno(Until::Fine), "D.S. al Fine")]
+    #[case(Marker::ToCoda, "To Coda")]
+    #[case(Marker::Segno, "Segno")]
+    fn display(marker: Marker, output: &str) {
+        assert_eq!(marker.to_string(), output);
+    }
+}
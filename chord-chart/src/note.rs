@@ -1,8 +1,11 @@
 use crate::{
-    error::Error,
+    diagnostic::{Diagnostic, Warning, WarningKind},
+    error::{Error, ErrorKind},
+    parse::{Dialect, ParseOptions},
+    render::{Accidentals, NoteNaming, RenderOptions},
     transpose::{Scale, Transpose},
 };
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Natural {
@@ -20,18 +23,57 @@ pub enum Accidental {
     Natural = 0,
     Flat = -1,
     Sharp = 1,
+    DoubleFlat = -2,
+    DoubleSharp = 2,
 }
 
 impl Accidental {
+    /// Spelling for notes transposed to a key with this accidental: flats after a flat,
+    /// sharps otherwise.
+    #[deprecated(note = "use `Note::scale` or `Key::scale`, which follow the key signature")]
     pub fn scale(&self) -> Scale {
-        match self {
-            Self::Flat => Scale::Minor,
-            _ => Scale::Major,
-        }
+        // F major spells every black key as a flat, E major as a sharp
+        let tonic = match self {
+            Self::Flat | Self::DoubleFlat => Note::new(Natural::F, Self::Natural),
+            _ => Note::new(Natural::E, Self::Natural),
+        };
+        Scale::major(&tonic)
+    }
+
+    fn is_double(&self) -> bool {
+        matches!(self, Self::DoubleFlat | Self::DoubleSharp)
+    }
+}
+
+impl Natural {
+    const ALL: [Self; 7] = [
+        Self::C,
+        Self::D,
+        Self::E,
+        Self::F,
+        Self::G,
+        Self::A,
+        Self::B,
+    ];
+
+    /// Fixed-do names, in the same order as `ALL`.
+    const SYLLABLES: [&'static str; 7] = ["Do", "Re", "Mi", "Fa", "Sol", "La", "Si"];
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|n| n == self).unwrap()
+    }
+
+    fn syllable(&self) -> &'static str {
+        Self::SYLLABLES[self.index()]
+    }
+
+    /// Natural that is `steps` letters above this one.
+    fn step(&self, steps: i32) -> Self {
+        Self::ALL[(self.index() as i32 + steps).rem_euclid(7) as usize].clone()
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Note {
     natural: Natural,
     accidental: Accidental,
@@ -52,6 +94,28 @@ enum Semitone {
     Eleven,
 }
 
+impl From<i32> for Semitone {
+    fn from(value: i32) -> Self {
+        use Semitone::*;
+
+        match value.rem_euclid(12) {
+            0 => Zero,
+            1 => One,
+            2 => Two,
+            3 => Three,
+            4 => Four,
+            5 => Five,
+            6 => Six,
+            7 => Seven,
+            8 => Eight,
+            9 => Nine,
+            10 => Ten,
+            11 => Eleven,
+            _ => unreachable!(),
+        }
+    }
+}
+
 impl Note {
     fn new(natural: Natural, accidental: Accidental) -> Self {
         Self {
@@ -64,11 +128,50 @@ impl Note {
         &self.accidental
     }
 
+    /// Major scale of this tonic used to spell transposed notes.
+    pub fn scale(&self) -> Scale {
+        Scale::major(self)
+    }
+
+    /// Position on the line of fifths, C is 0, G is 1, F is -1.
+    /// For a major key tonic it is the number of sharps (or flats, if negative).
+    pub(crate) fn fifths(&self) -> i32 {
+        use self::Natural::*;
+
+        let natural = match self.natural {
+            F => -1,
+            C => 0,
+            G => 1,
+            D => 2,
+            A => 3,
+            E => 4,
+            B => 5,
+        };
+        natural + 7 * self.accidental.clone() as i32
+    }
+
+    /// Number of letters from this note up to `note`.
+    pub(crate) fn steps_to(&self, note: &Self) -> i32 {
+        (note.natural.index() as i32 - self.natural.index() as i32).rem_euclid(7)
+    }
+
     pub fn parse(natural_ch: Option<char>, accidental_ch: Option<char>) -> Result<Self, Error> {
+        match natural_ch {
+            Some(c) => {
+                let s: String = [c].into_iter().chain(accidental_ch).collect();
+                Self::parse_prefix(&s).map(|(note, _)| note)
+            }
+            None => Err(Error::new(ErrorKind::NoNatural, "", 0..0)),
+        }
+    }
+
+    /// Parses note at the start of `s` and returns the rest of it.
+    pub(crate) fn parse_prefix(s: &str) -> Result<(Self, &str), Error> {
         use self::Natural::*;
         use Accidental::*;
 
-        let natural = match natural_ch {
+        let mut chars = s.chars();
+        let natural = match chars.next() {
             Some(c) => match c.to_ascii_uppercase() {
                 'C' => C,
                 'D' => D,
@@ -78,48 +181,191 @@ impl Note {
                 'A' => A,
                 'B' => B,
                 'H' => {
-                    if let Some(c) = accidental_ch {
-                        match c {
-                            'b' | 'B' => return Err(Error::InvalidNote("Hb")),
-                            '#' => return Err(Error::InvalidNote("H#")),
-                            _ => (),
+                    return match Self::parse_accidental(chars.as_str()) {
+                        (Flat | DoubleFlat, len) => {
+                            Err(Error::new(ErrorKind::InvalidNote("Hb"), s, 0..1 + len))
                         }
-                    }
-
-                    return Ok(Self::new(B, Natural));
+                        (Sharp | DoubleSharp, len) => {
+                            Err(Error::new(ErrorKind::InvalidNote("H#"), s, 0..1 + len))
+                        }
+                        (Natural, _) => Ok((Self::new(B, Natural), chars.as_str())),
+                    };
                 }
-                _ => return Err(Error::InvalidNatural(c)),
+                _ => return Err(Self::natural_error(s)),
             },
-            None => return Err(Error::NoNatural),
+            None => return Err(Self::natural_error(s)),
         };
 
-        let accidental = match accidental_ch {
-            Some(c) => match c.to_ascii_lowercase() {
-                'b' => Flat,
-                '#' => Sharp,
-                _ => Natural,
+        let rest = chars.as_str();
+        let (accidental, len) = Self::parse_accidental(rest);
+        Ok((Self::new(natural, accidental), &rest[len..]))
+    }
+
+    /// Parses note written in the dialect of `options`, "Fis" or "Es" in German.
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_prefix_with(s, options).map(|(note, _)| note)
+    }
+
+    /// Like `from_str_with`, but warns about text after the note that is ignored: "w" in "Aw".
+    pub fn from_str_recovering(s: &str, options: &ParseOptions) -> (Option<Self>, Vec<Diagnostic>) {
+        match Self::parse_prefix_with(s, options) {
+            Ok((note, "")) => (Some(note), Vec::new()),
+            Ok((note, rest)) => {
+                let kind = WarningKind::IgnoredAfterNote(rest.into());
+                let warning = Warning::new(kind, s, s.len() - rest.len()..s.len());
+                (Some(note), vec![warning.into()])
+            }
+            Err(error) => (None, vec![error.into()]),
+        }
+    }
+
+    /// Like `parse_prefix`, but reads note names of the dialect in `options`.
+    pub(crate) fn parse_prefix_with<'a>(
+        s: &'a str,
+        options: &ParseOptions,
+    ) -> Result<(Self, &'a str), Error> {
+        use self::Natural::*;
+        use Accidental::*;
+
+        match options.dialect {
+            Dialect::English => return Self::parse_prefix(s),
+            Dialect::Solfege => {
+                let (natural, rest) = Self::parse_syllable(s)?;
+                let (accidental, len) = Self::parse_accidental(rest);
+                return Ok((Self::new(natural, accidental), &rest[len..]));
+            }
+            Dialect::German | Dialect::Dutch => (),
+        }
+
+        let mut chars = s.chars();
+        let (natural, is_german_b) = match chars.next() {
+            Some(c) => match c.to_ascii_uppercase() {
+                'C' => (C, false),
+                'D' => (D, false),
+                'E' => (E, false),
+                'F' => (F, false),
+                'G' => (G, false),
+                'A' => (A, false),
+                'B' => (B, options.dialect == Dialect::German),
+                'H' => (B, false),
+                _ => return Err(Self::natural_error(s)),
             },
-            _ => Natural,
+            None => return Err(Self::natural_error(s)),
         };
 
-        match (&natural, &accidental) {
-            (C, Flat) => return Err(Error::InvalidNote("Cb")),
-            (E, Sharp) => return Err(Error::InvalidNote("E#")),
-            (F, Flat) => return Err(Error::InvalidNote("Fb")),
-            (B, Sharp) => return Err(Error::InvalidNote("B#")),
-            _ => (),
+        let rest = chars.as_str();
+        let (accidental, len) = match Self::parse_accidental(rest) {
+            (Natural, _) => Self::parse_suffix(&natural, rest),
+            parsed => parsed,
+        };
+
+        // German B is already flat, so it takes no accidentals
+        if is_german_b {
+            return match accidental {
+                Natural => Ok((Self::new(B, Flat), rest)),
+                Flat | DoubleFlat => Err(Error::new(ErrorKind::InvalidNote("Bb"), s, 0..1 + len)),
+                Sharp | DoubleSharp => Err(Error::new(ErrorKind::InvalidNote("B#"), s, 0..1 + len)),
+            };
+        }
+        Ok((Self::new(natural, accidental), &rest[len..]))
+    }
+
+    /// Fixed-do syllable at the start of `s` and the rest of it: "Sol#m" is G and "#m".
+    fn parse_syllable(s: &str) -> Result<(Natural, &str), Error> {
+        use self::Natural::*;
+
+        let syllables = [
+            ("do", C),
+            ("re", D),
+            ("ré", D),
+            ("mi", E),
+            ("fa", F),
+            ("sol", G),
+            ("la", A),
+            ("si", B),
+            ("ti", B),
+        ];
+        // Lowercase text can be longer or shorter in bytes, so it's compared in place
+        match syllables
+            .into_iter()
+            .find(|(p, _)| s.get(..p.len()).is_some_and(|h| h.to_lowercase() == *p))
+        {
+            Some((syllable, natural)) => Ok((natural, &s[syllable.len()..])),
+            None => Err(Self::natural_error(s)),
         }
+    }
 
-        Ok(Self::new(natural, accidental))
+    /// Error about the first character of `s` that should have been a natural.
+    fn natural_error(s: &str) -> Error {
+        match s.chars().next() {
+            Some(c) => Error::new(ErrorKind::InvalidNatural(c), s, 0..c.len_utf8()),
+            None => Error::new(ErrorKind::NoNatural, s, 0..0),
+        }
     }
 
-    fn from_semitone_and_scale(semitone: &Semitone, scale: &Scale) -> Self {
+    /// German and Dutch accidental suffix and its length: "is", "es" and "s" after E and A.
+    fn parse_suffix(natural: &Natural, s: &str) -> (Accidental, usize) {
+        use Accidental::*;
+
+        let suffixes = [
+            ("isis", DoubleSharp),
+            ("is", Sharp),
+            ("eses", DoubleFlat),
+            ("es", Flat),
+        ];
+        if let Some((suffix, accidental)) = suffixes.into_iter().find(|(p, _)| s.starts_with(p)) {
+            return (accidental, suffix.len());
+        }
+
+        // "Es" and "As", but "Esus4" is E suspended
+        let is_vowel = matches!(natural, self::Natural::E | self::Natural::A);
+        if !is_vowel || s.starts_with("sus") {
+            (Natural, 0)
+        } else if s.starts_with("ses") {
+            (DoubleFlat, 3)
+        } else if s.starts_with('s') {
+            (Flat, 1)
+        } else {
+            (Natural, 0)
+        }
+    }
+
+    /// Accidental at the start of `s` and its length in bytes, ASCII or Unicode.
+    fn parse_accidental(s: &str) -> (Accidental, usize) {
+        use Accidental::*;
+
+        let lowercase: String = s.chars().take(2).collect::<String>().to_ascii_lowercase();
+        let accidentals = [
+            ("bb", DoubleFlat),
+            ("##", DoubleSharp),
+            ("♭♭", DoubleFlat),
+            ("♯♯", DoubleSharp),
+            ("𝄫", DoubleFlat),
+            ("𝄪", DoubleSharp),
+            ("b", Flat),
+            ("#", Sharp),
+            ("♭", Flat),
+            ("♯", Sharp),
+        ];
+
+        if s.starts_with('x') {
+            return (DoubleSharp, 1);
+        }
+        accidentals
+            .into_iter()
+            .find(|(prefix, _)| lowercase.starts_with(prefix))
+            .map_or((Natural, 0), |(prefix, accidental)| {
+                (accidental, prefix.len())
+            })
+    }
+
+    fn from_semitone_and_sharps(semitone: &Semitone, sharps: bool) -> Self {
         use self::Natural::*;
         use Accidental::*;
         use Semitone::*;
 
-        let (natural, accidental) = match scale {
-            Scale::Major => match semitone {
+        let (natural, accidental) = if sharps {
+            match semitone {
                 Zero => (C, Natural),
                 One => (C, Sharp),
                 Two => (D, Natural),
@@ -132,8 +378,9 @@ impl Note {
                 Nine => (A, Natural),
                 Ten => (A, Sharp),
                 Eleven => (B, Natural),
-            },
-            Scale::Minor => match semitone {
+            }
+        } else {
+            match semitone {
                 Zero => (C, Natural),
                 One => (D, Flat),
                 Two => (D, Natural),
@@ -146,12 +393,81 @@ impl Note {
                 Nine => (A, Natural),
                 Ten => (B, Flat),
                 Eleven => (B, Natural),
-            },
+            }
         };
 
         Self::new(natural, accidental)
     }
 
+    /// Pitch class, C is 0.
+    pub(crate) fn semitone(&self) -> i32 {
+        (self.natural.clone() as i32 + self.accidental.clone() as i32).rem_euclid(12)
+    }
+
+    /// Note that is `steps` letters and `semitones` above this one,
+    /// falls back to enharmonic spelling if exact one can't be represented.
+    pub(crate) fn add_interval(&self, steps: i32, semitones: i32) -> Self {
+        let natural = self.natural.step(steps);
+        let semitone = self.semitone() + semitones;
+
+        let accidental = match Self::offset(&natural, semitone) {
+            -2 => Accidental::DoubleFlat,
+            -1 => Accidental::Flat,
+            0 => Accidental::Natural,
+            1 => Accidental::Sharp,
+            2 => Accidental::DoubleSharp,
+            offset => return Self::from_semitone(semitone, offset > 0),
+        };
+        Self::new(natural, accidental)
+    }
+
+    /// Like `add_interval`, but gives up when spelling needs a double accidental.
+    pub(crate) fn try_add_interval(&self, steps: i32, semitones: i32) -> Option<Self> {
+        Some(self.add_interval(steps, semitones)).filter(|note| !note.accidental.is_double())
+    }
+
+    /// The simplest spelling of a pitch class, with sharps or flats.
+    pub(crate) fn from_semitone(semitone: i32, sharps: bool) -> Self {
+        Self::from_semitone_and_sharps(&Semitone::from(semitone), sharps)
+    }
+
+    /// Semitones from `natural` to the nearest `semitone`, within -6..6.
+    fn offset(natural: &Natural, semitone: i32) -> i32 {
+        (semitone - natural.clone() as i32 + 6).rem_euclid(12) - 6
+    }
+
+    /// Writes the note with B, H or Si and ASCII or Unicode accidentals.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        use self::Natural::*;
+        use Accidental::*;
+
+        let natural = match self.natural {
+            _ if options.note_naming == NoteNaming::Solfege => self.natural.syllable(),
+            C => "C",
+            D => "D",
+            E => "E",
+            F => "F",
+            G => "G",
+            A => "A",
+            B if self.accidental == Natural && options.note_naming == NoteNaming::German => "H",
+            B => "B",
+        };
+
+        let accidental = match (&self.accidental, options.accidentals) {
+            (Natural, _) => "",
+            (Flat, Accidentals::Ascii) => "b",
+            (Sharp, Accidentals::Ascii) => "#",
+            (DoubleFlat, Accidentals::Ascii) => "bb",
+            (DoubleSharp, Accidentals::Ascii) => "##",
+            (Flat, Accidentals::Unicode) => "♭",
+            (Sharp, Accidentals::Unicode) => "♯",
+            (DoubleFlat, Accidentals::Unicode) => "𝄫",
+            (DoubleSharp, Accidentals::Unicode) => "𝄪",
+        };
+
+        String::from(natural) + accidental
+    }
+
     pub fn get_semitones_diff(&self, note: &Self) -> i32 {
         (note.natural.clone() as i32 + note.accidental.clone() as i32)
             - (self.natural.clone() as i32 + self.accidental.clone() as i32)
@@ -162,74 +478,28 @@ impl FromStr for Note {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-        Note::parse(chars.next(), chars.next())
+        Note::parse_prefix(s).map(|(note, _)| note)
     }
 }
 
 impl Transpose for Note {
     fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
-        use Semitone::*;
-
-        let semitone =
-            match (self.natural.clone() as i32 + self.accidental.clone() as i32 + semitone_incr)
-                .rem_euclid(12)
-            {
-                0 => Zero,
-                1 => One,
-                2 => Two,
-                3 => Three,
-                4 => Four,
-                5 => Five,
-                6 => Six,
-                7 => Seven,
-                8 => Eight,
-                9 => Nine,
-                10 => Ten,
-                11 => Eleven,
-                _ => unreachable!(),
-            };
-
-        Self::from_semitone_and_scale(&semitone, scale)
+        scale.spell(self.semitone() + semitone_incr)
     }
 }
 
-impl ToString for Note {
-    fn to_string(&self) -> String {
-        use self::Natural::*;
-        use Accidental::*;
-
-        if let (B, Natural) = (&self.natural, &self.accidental) {
-            "H".into()
-        } else {
-            let natural = match self.natural {
-                C => "C",
-                D => "D",
-                E => "E",
-                F => "F",
-                G => "G",
-                A => "A",
-                B => "B",
-            };
-
-            let accidental = match self.accidental {
-                Natural => "",
-                Flat => "b",
-                Sharp => "#",
-            };
-
-            String::from(natural) + accidental
-        }
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Accidental::*;
-    use super::Error::*;
     use super::Natural::*;
-    use super::Scale::*;
     use super::*;
+    use crate::error::ErrorKind::*;
 
     use test_case::case;
 
@@ -239,35 +509,160 @@ mod tests {
     #[case(A, Natural, "Aw", "A")]
     #[case(B, Natural, "H", "H")]
     #[case(B, Natural, "Hw", "H")]
+    #[case(C, Flat, "cB", "Cb")]
+    #[case(E, Sharp, "E#", "E#")]
+    #[case(F, Flat, "Fb", "Fb")]
+    #[case(B, Sharp, "B#", "B#")]
+    #[case(B, DoubleFlat, "Bbb", "Bbb")]
+    #[case(F, DoubleSharp, "F##", "F##")]
+    #[case(F, DoubleSharp, "Fx", "F##")]
     fn basics_ok(natural: super::Natural, accidental: Accidental, input: &str, output: &str) {
         let value = Note::from_str(input).unwrap();
         assert_eq!(value, Note::new(natural, accidental));
         assert_eq!(value.to_string(), output);
     }
 
+    #[case("B", NoteNaming::English, Accidentals::Ascii, "B")]
+    #[case("H", NoteNaming::German, Accidentals::Unicode, "H")]
+    #[case("Bb", NoteNaming::German, Accidentals::Unicode, "B♭")]
+    #[case("F#", NoteNaming::English, Accidentals::Unicode, "F♯")]
+    #[case("Ebb", NoteNaming::English, Accidentals::Unicode, "E𝄫")]
+    #[case("Cx", NoteNaming::English, Accidentals::Unicode, "C𝄪")]
+    #[case("G#", NoteNaming::Solfege, Accidentals::Ascii, "Sol#")]
+    #[case("H", NoteNaming::Solfege, Accidentals::Ascii, "Si")]
+    #[case("Bb", NoteNaming::Solfege, Accidentals::Unicode, "Si♭")]
+    fn render(input: &str, note_naming: NoteNaming, accidentals: Accidentals, output: &str) {
+        let options = RenderOptions {
+            note_naming,
+            accidentals,
+            ..Default::default()
+        };
+        assert_eq!(Note::from_str(input).unwrap().render(&options), output);
+    }
+
+    #[case("B♭", B, Flat)]
+    #[case("F♯", F, Sharp)]
+    #[case("E𝄫", E, DoubleFlat)]
+    #[case("C𝄪", C, DoubleSharp)]
+    #[case("D♭♭", D, DoubleFlat)]
+    fn unicode(input: &str, natural: super::Natural, accidental: Accidental) {
+        assert_eq!(
+            Note::from_str(input).unwrap(),
+            Note::new(natural, accidental)
+        );
+    }
+
+    #[case(Dialect::German, "B", B, Flat, "")]
+    #[case(Dialect::German, "H", B, Natural, "")]
+    #[case(Dialect::German, "His", B, Sharp, "")]
+    #[case(Dialect::German, "Fism7", F, Sharp, "m7")]
+    #[case(Dialect::German, "Cisis", C, DoubleSharp, "")]
+    #[case(Dialect::German, "Des", D, Flat, "")]
+    #[case(Dialect::German, "Geses", G, DoubleFlat, "")]
+    #[case(Dialect::German, "Es", E, Flat, "")]
+    #[case(Dialect::German, "Asm", A, Flat, "m")]
+    #[case(Dialect::German, "Ases", A, DoubleFlat, "")]
+    #[case(Dialect::German, "Esus4", E, Natural, "sus4")]
+    #[case(Dialect::German, "Essus4", E, Flat, "sus4")]
+    #[case(Dialect::German, "Ebm", E, Flat, "m")]
+    #[case(Dialect::German, "F#", F, Sharp, "")]
+    #[case(Dialect::Dutch, "B", B, Natural, "")]
+    #[case(Dialect::Dutch, "Bes", B, Flat, "")]
+    #[case(Dialect::Dutch, "Bis7", B, Sharp, "7")]
+    #[case(Dialect::English, "Es", E, Natural, "s")]
+    #[case(Dialect::Solfege, "Do", C, Natural, "")]
+    #[case(Dialect::Solfege, "Sol#m7", G, Sharp, "m7")]
+    #[case(Dialect::Solfege, "sib", B, Flat, "")]
+    #[case(Dialect::Solfege, "Ré♭", D, Flat, "")]
+    #[case(Dialect::Solfege, "TI", B, Natural, "")]
+    #[case(Dialect::Solfege, "Lam", A, Natural, "m")]
+    fn dialect_ok(
+        dialect: Dialect,
+        input: &str,
+        natural: super::Natural,
+        accidental: Accidental,
+        rest: &str,
+    ) {
+        let options = ParseOptions {
+            dialect,
+            ..Default::default()
+        };
+        assert_eq!(
+            Note::parse_prefix_with(input, &options).unwrap(),
+            (Note::new(natural, accidental), rest)
+        );
+    }
+
+    #[case(Dialect::German, "Bb", InvalidNote("Bb"))]
+    #[case(Dialect::German, "Bes", InvalidNote("Bb"))]
+    #[case(Dialect::German, "B#", InvalidNote("B#"))]
+    #[case(Dialect::Dutch, "W", InvalidNatural('W'))]
+    #[case(Dialect::Dutch, "", NoNatural)]
+    #[case(Dialect::Solfege, "C", InvalidNatural('C'))]
+    #[case(Dialect::Solfege, "So", InvalidNatural('S'))]
+    #[case(Dialect::Solfege, "", NoNatural)]
+    #[case(Dialect::Solfege, "sİ", InvalidNatural('s') ; "lowercase longer than text")]
+    #[case(Dialect::Solfege, "ẞi", InvalidNatural('ẞ') ; "lowercase shorter than text")]
+    fn dialect_err(dialect: Dialect, input: &str, error: ErrorKind) {
+        let options = ParseOptions {
+            dialect,
+            ..Default::default()
+        };
+        assert_eq!(
+            Note::from_str_with(input, &options).unwrap_err().kind(),
+            &error
+        );
+    }
+
+    #[test]
+    fn recovering() {
+        let options = ParseOptions::default();
+        let (note, diagnostics) = Note::from_str_recovering("Aw", &options);
+        assert_eq!(note, Some(Note::new(A, Natural)));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span().token(), "w");
+        assert!(!diagnostics[0].is_error());
+
+        let (note, diagnostics) = Note::from_str_recovering("A#", &options);
+        assert_eq!(note, Some(Note::new(A, Sharp)));
+        assert!(diagnostics.is_empty());
+
+        let (note, diagnostics) = Note::from_str_recovering("W", &options);
+        assert_eq!(note, None);
+        assert!(diagnostics[0].is_error());
+    }
+
     #[case("w", InvalidNatural('w'))]
     #[case("", NoNatural)]
     #[case("Hb", InvalidNote("Hb"))]
     #[case("H#", InvalidNote("H#"))]
-    #[case("cB", InvalidNote("Cb"))]
-    #[case("E#", InvalidNote("E#"))]
-    #[case("Fb", InvalidNote("Fb"))]
-    #[case("B#", InvalidNote("B#"))]
-    fn basics_err(input: &str, error: Error) {
+    #[case("Hx", InvalidNote("H#"))]
+    #[case("H♭", InvalidNote("Hb"))]
+    fn basics_err(input: &str, error: ErrorKind) {
         let value = Note::from_str(input).unwrap_err();
-        assert_eq!(value, error);
-    }
-
-    #[case("A", 12, Major, "A")]
-    #[case("C", 1, Major, "C#")]
-    #[case("C", -1, Minor, "H")]
-    #[case("D", 2, Major, "E")]
-    #[case("E", 2, Major, "F#")]
-    #[case("E", 2, Minor, "Gb")]
-    #[case("A", 11, Major, "G#")]
-    #[case("Bb", 1, Minor, "H")]
-    fn transpose(input: &str, semitone_incr: i32, scale: Scale, output: &str) {
+        assert_eq!(value.kind(), &error);
+    }
+
+    #[case("A", 12, "C", "A")]
+    #[case("C", 1, "D", "C#")]
+    #[case("C", -1, "F", "H")]
+    #[case("D", 2, "C", "E")]
+    #[case("E", 2, "G", "F#")]
+    #[case("E", 2, "Db", "Gb")]
+    #[case("A", 11, "A", "G#")]
+    #[case("Bb", 1, "E", "H")]
+    #[case("C", 10, "F", "Bb")]
+    #[case("C", 6, "C", "F#")]
+    #[case("C", 8, "C", "Ab")]
+    #[case("C", 8, "G#", "Ab")]
+    #[case("C", 3, "A", "D#")]
+    #[case("C", 11, "Gb", "Cb")]
+    #[case("C", 5, "C#", "E#")]
+    #[case("C", 9, "Gb", "A")]
+    #[case("Cb", 0, "E", "H")]
+    fn transpose(input: &str, semitone_incr: i32, key: &str, output: &str) {
         let value = Note::from_str(input).unwrap();
+        let scale = Note::from_str(key).unwrap().scale();
         assert_eq!(value.transpose(&semitone_incr, &scale).to_string(), output)
     }
 
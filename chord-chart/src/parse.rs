@@ -0,0 +1,63 @@
+use crate::error::{Error, ErrorKind};
+use std::str::FromStr;
+
+/// How notes in a chart are read.
+/// Default is what `FromStr` does: English names, with H accepted as B.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub dialect: Dialect,
+    /// Rejects chord symbols that are not a known quality, "Cxyz" or "Gmajj7",
+    /// instead of skipping what can't be read.
+    pub strict: bool,
+}
+
+/// Note names of the chart author.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Dialect {
+    /// "Bb", "F#"; B and H are both B natural.
+    #[default]
+    English,
+    /// German and Scandinavian: "B" is B flat, "H" is B natural, "Fis", "Es", "As".
+    German,
+    /// "Bes" is B flat, "B" is B natural, "Fis", "Es", "As".
+    Dutch,
+    /// Fixed-do solfège: "Do", "Sol#", "Sib", "Ré".
+    Solfege,
+}
+
+impl FromStr for Dialect {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "english" => Ok(Self::English),
+            "german" | "scandinavian" => Ok(Self::German),
+            "dutch" => Ok(Self::Dutch),
+            "solfege" | "solfège" => Ok(Self::Solfege),
+            _ => Err(Error::trimmed(ErrorKind::InvalidOption(s.trim().into()), s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("english", Dialect::English)]
+    #[case(" German", Dialect::German)]
+    #[case("scandinavian", Dialect::German)]
+    #[case("DUTCH", Dialect::Dutch)]
+    #[case("solfege", Dialect::Solfege)]
+    fn dialect_ok(input: &str, output: Dialect) {
+        assert_eq!(Dialect::from_str(input).unwrap(), output);
+    }
+
+    #[test]
+    fn dialect_err() {
+        assert_eq!(
+            Dialect::from_str("french").unwrap_err().kind(),
+            &ErrorKind::InvalidOption("french".into())
+        );
+    }
+}
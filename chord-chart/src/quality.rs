@@ -0,0 +1,598 @@
+use crate::error::{Error, ErrorKind};
+use std::ops::Range;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Triad {
+    #[default]
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Suspension {
+    Second,
+    Fourth,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Seventh {
+    Minor,
+    Major,
+    Diminished,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Extension {
+    Ninth,
+    Eleventh,
+    Thirteenth,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Alteration {
+    FlatFifth,
+    SharpFifth,
+    FlatNinth,
+    SharpNinth,
+    SharpEleventh,
+    FlatThirteenth,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Degree {
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Ninth,
+    Eleventh,
+    Thirteenth,
+}
+
+/// Structured meaning of everything between chord root and bass note,
+/// so "Cmaj7", "CM7" and "CΔ7" all end up being the same quality.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ChordQuality {
+    triad: Triad,
+    suspension: Option<Suspension>,
+    seventh: Option<Seventh>,
+    extension: Option<Extension>,
+    alterations: Vec<Alteration>,
+    added: Vec<Degree>,
+    omitted: Vec<Degree>,
+}
+
+impl ChordQuality {
+    pub fn triad(&self) -> Triad {
+        self.triad
+    }
+
+    pub fn suspension(&self) -> Option<Suspension> {
+        self.suspension
+    }
+
+    pub fn seventh(&self) -> Option<Seventh> {
+        self.seventh
+    }
+
+    pub fn extension(&self) -> Option<Extension> {
+        self.extension
+    }
+
+    pub fn alterations(&self) -> &[Alteration] {
+        &self.alterations
+    }
+
+    pub fn added(&self) -> &[Degree] {
+        &self.added
+    }
+
+    pub fn omitted(&self) -> &[Degree] {
+        &self.omitted
+    }
+
+    pub(crate) fn of_triad(triad: Triad) -> Self {
+        Self {
+            triad,
+            ..Default::default()
+        }
+    }
+
+    /// Chord tones as (letter steps, semitones) above the root, lowest first.
+    pub(crate) fn intervals(&self) -> Vec<(i32, i32)> {
+        use self::Alteration::*;
+        use self::Degree::*;
+
+        let has = |alteration| self.alterations.contains(&alteration);
+        let mut intervals = vec![(0, 0)];
+
+        if !self.omitted.contains(&Third) {
+            intervals.push(match (self.suspension, self.triad) {
+                (Some(Suspension::Second), _) => (1, 2),
+                (Some(Suspension::Fourth), _) => (3, 5),
+                (None, Triad::Minor | Triad::Diminished) => (2, 3),
+                (None, Triad::Major | Triad::Augmented) => (2, 4),
+            });
+        }
+        if !self.omitted.contains(&Fifth) {
+            intervals.push(match self.triad {
+                _ if has(FlatFifth) => (4, 6),
+                _ if has(SharpFifth) => (4, 8),
+                Triad::Diminished => (4, 6),
+                Triad::Augmented => (4, 8),
+                Triad::Major | Triad::Minor => (4, 7),
+            });
+        }
+        if let Some(seventh) = self.seventh {
+            intervals.push(match seventh {
+                Seventh::Minor => (6, 10),
+                Seventh::Major => (6, 11),
+                Seventh::Diminished => (6, 9),
+            });
+        }
+
+        let mut degrees = self.added.clone();
+        match self.extension {
+            Some(Extension::Ninth) => degrees.push(Ninth),
+            Some(Extension::Eleventh) => degrees.extend([Ninth, Eleventh]),
+            // Natural eleventh clashes with major third, so it is left out
+            Some(Extension::Thirteenth) if self.triad == Triad::Minor => {
+                degrees.extend([Ninth, Eleventh, Thirteenth])
+            }
+            Some(Extension::Thirteenth) => degrees.extend([Ninth, Thirteenth]),
+            None => (),
+        }
+        for alteration in &self.alterations {
+            let (degree, interval) = match alteration {
+                FlatNinth => (Ninth, (8, 13)),
+                SharpNinth => (Ninth, (8, 15)),
+                SharpEleventh => (Eleventh, (10, 18)),
+                FlatThirteenth => (Thirteenth, (12, 20)),
+                // Already applied to the fifth
+                FlatFifth | SharpFifth => continue,
+            };
+            degrees.retain(|d| d != &degree);
+            intervals.push(interval);
+        }
+        for degree in degrees {
+            intervals.push(match degree {
+                Second => (1, 2),
+                Third => (2, 4),
+                Fourth => (3, 5),
+                Fifth => (4, 7),
+                Sixth => (5, 9),
+                Ninth => (8, 14),
+                Eleventh => (10, 17),
+                Thirteenth => (12, 21),
+            });
+        }
+
+        intervals.sort_by_key(|&(_, semitones)| semitones);
+        intervals.dedup();
+        intervals
+    }
+
+    /// Unrecognized symbols are skipped.
+    pub fn parse(symbols: &str) -> Self {
+        Parser::new(symbols).run().0
+    }
+
+    /// Like `parse`, but fails on unrecognized symbols and unbalanced parentheses.
+    pub fn parse_strict(symbols: &str) -> Result<Self, Error> {
+        match Parser::new(symbols).run() {
+            (quality, None) => Ok(quality),
+            (_, Some(bytes)) => Err(Error::new(
+                ErrorKind::InvalidQuality(symbols.into()),
+                symbols,
+                bytes,
+            )),
+        }
+    }
+}
+
+struct Parser<'a> {
+    quality: ChordQuality,
+    major_seventh: bool,
+    symbols: &'a str,
+    rest: &'a str,
+    start: bool,
+    /// Offsets of parentheses that are not closed yet.
+    open: Vec<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(symbols: &'a str) -> Self {
+        Self {
+            quality: ChordQuality::default(),
+            major_seventh: false,
+            symbols,
+            rest: symbols,
+            start: true,
+            open: Vec::new(),
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.symbols.len() - self.rest.len()
+    }
+
+    /// Parses all symbols, returning where the first unrecognized one is.
+    fn run(mut self) -> (ChordQuality, Option<Range<usize>>) {
+        let mut unknown = None;
+        while !self.rest.is_empty() {
+            let start = self.offset();
+            if !self.token() {
+                if self.offset() == start {
+                    let mut chars = self.rest.chars();
+                    chars.next();
+                    self.rest = chars.as_str();
+                }
+                unknown.get_or_insert(start..self.offset());
+            }
+            self.start = false;
+        }
+
+        if let Some(&start) = self.open.first() {
+            if !matches!(&unknown, Some(u) if u.start < start) {
+                unknown = Some(start..start + 1);
+            }
+        }
+        (self.finish(), unknown)
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn eat_any(&mut self, prefixes: &[&str]) -> bool {
+        prefixes.iter().any(|p| self.eat(p))
+    }
+
+    fn followed_by_digit(&self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => rest.starts_with(|c: char| c.is_ascii_digit()),
+            None => false,
+        }
+    }
+
+    fn number(&mut self) -> Option<u8> {
+        let len = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let (digits, rest) = self.rest.split_at(len);
+        let value = digits.parse().ok()?;
+        self.rest = rest;
+        Some(value)
+    }
+
+    /// Returns `false` when nothing could be recognized at current position.
+    fn token(&mut self) -> bool {
+        use self::Degree::*;
+
+        if self.eat_any(&[",", " "]) {
+            return true;
+        }
+        if self.eat("(") {
+            self.open.push(self.offset() - 1);
+            return true;
+        }
+        if !self.open.is_empty() && self.eat(")") {
+            self.open.pop();
+            return true;
+        }
+
+        if self.eat_any(&["maj", "Maj", "MAJ", "M"])
+            || (self.followed_by_digit("ma") && self.eat("ma"))
+        {
+            self.major_seventh = true;
+            return true;
+        }
+        if self.eat_any(&["Δ", "∆"]) {
+            self.major_seventh = true;
+            self.quality.seventh = Some(Seventh::Major);
+            return true;
+        }
+        // "-5" and "+5" alter the fifth, "-7" and "+7" are minor and augmented sevenths
+        let triad_sign = |p: &mut Self, sign| {
+            p.start
+                && !p
+                    .rest
+                    .strip_prefix(sign)
+                    .is_some_and(|r| r.starts_with('5'))
+                && p.eat(sign)
+        };
+        if self.eat_any(&["min", "mi", "m"]) || triad_sign(self, "-") {
+            self.quality.triad = Triad::Minor;
+            return true;
+        }
+        if self.eat_any(&["dim", "°"]) || (self.start && self.eat("o")) {
+            self.quality.triad = Triad::Diminished;
+            return true;
+        }
+        if self.eat_any(&["ø", "Ø"]) {
+            self.quality.triad = Triad::Diminished;
+            self.quality.seventh = Some(Seventh::Minor);
+            return true;
+        }
+        if self.eat("aug") || triad_sign(self, "+") {
+            self.quality.triad = Triad::Augmented;
+            return true;
+        }
+        if self.eat("sus") {
+            self.quality.suspension = Some(if self.eat("2") {
+                Suspension::Second
+            } else {
+                self.eat("4");
+                Suspension::Fourth
+            });
+            return true;
+        }
+        // "6/9" and "7/6" add the degree after the slash
+        if self.followed_by_digit("/") {
+            self.eat("/");
+            return match self.number() {
+                Some(number) => self.add(number),
+                None => false,
+            };
+        }
+        if self.eat("add") {
+            let flat = self.eat_any(&["b", "♭"]);
+            let sharp = !flat && self.eat_any(&["#", "♯"]);
+            return match (self.number(), flat, sharp) {
+                (Some(number), false, false) => self.add(number),
+                (Some(number), _, _) => self.alter(number, flat),
+                (None, _, _) => false,
+            };
+        }
+        if self.eat_any(&["omit", "no"]) {
+            return match self.number() {
+                Some(3) => {
+                    self.quality.omitted.push(Third);
+                    true
+                }
+                Some(5) => {
+                    self.quality.omitted.push(Fifth);
+                    true
+                }
+                _ => false,
+            };
+        }
+
+        let flat = ["b", "♭", "-"].iter().any(|p| self.followed_by_digit(p));
+        let sharp = ["#", "♯", "+"].iter().any(|p| self.followed_by_digit(p));
+        if flat || sharp {
+            self.rest = &self.rest[self.rest.chars().next().map_or(0, char::len_utf8)..];
+            return match self.number() {
+                Some(number) => self.alter(number, flat),
+                None => false,
+            };
+        }
+
+        match self.number() {
+            Some(number) => self.degree(number),
+            None => false,
+        }
+    }
+
+    fn degree(&mut self, number: u8) -> bool {
+        use self::Degree::*;
+
+        let extension = match number {
+            2 => {
+                self.quality.added.push(Second);
+                return true;
+            }
+            4 => {
+                self.quality.suspension = Some(Suspension::Fourth);
+                return true;
+            }
+            5 => {
+                self.quality.omitted.push(Third);
+                return true;
+            }
+            6 => {
+                self.quality.added.push(Sixth);
+                return true;
+            }
+            // Shorthand for "6/9"
+            69 => {
+                self.quality.added.extend([Sixth, Ninth]);
+                return true;
+            }
+            7 => None,
+            9 => Some(Extension::Ninth),
+            11 => Some(Extension::Eleventh),
+            13 => Some(Extension::Thirteenth),
+            _ => return false,
+        };
+
+        self.quality.seventh = Some(if self.major_seventh {
+            Seventh::Major
+        } else if self.quality.triad == Triad::Diminished && self.quality.seventh.is_none() {
+            Seventh::Diminished
+        } else {
+            self.quality.seventh.unwrap_or(Seventh::Minor)
+        });
+        self.quality.extension = extension;
+        true
+    }
+
+    fn add(&mut self, number: u8) -> bool {
+        use self::Degree::*;
+
+        let degree = match number {
+            2 => Second,
+            4 => Fourth,
+            6 => Sixth,
+            9 => Ninth,
+            11 => Eleventh,
+            13 => Thirteenth,
+            _ => return false,
+        };
+        self.quality.added.push(degree);
+        true
+    }
+
+    fn alter(&mut self, number: u8, flat: bool) -> bool {
+        use self::Alteration::*;
+
+        let alteration = match (number, flat) {
+            (5, true) => FlatFifth,
+            (5, false) => SharpFifth,
+            (9, true) => FlatNinth,
+            (9, false) => SharpNinth,
+            (11, false) => SharpEleventh,
+            (6 | 13, true) => FlatThirteenth,
+            _ => return false,
+        };
+        self.quality.alterations.push(alteration);
+        true
+    }
+
+    fn finish(self) -> ChordQuality {
+        let mut quality = self.quality;
+
+        // "m7b5" is the same thing as "ø7", and "7#5" is "+7"
+        let normalized = match quality.triad {
+            Triad::Minor => Some((Alteration::FlatFifth, Triad::Diminished)),
+            Triad::Major => Some((Alteration::SharpFifth, Triad::Augmented)),
+            _ => None,
+        };
+        if let Some((alteration, triad)) = normalized {
+            if quality.alterations.contains(&alteration) {
+                quality.alterations.retain(|a| a != &alteration);
+                quality.triad = triad;
+            }
+        }
+
+        quality.alterations.sort();
+        quality.alterations.dedup();
+        quality.added.sort();
+        quality.added.dedup();
+        quality.omitted.sort();
+        quality.omitted.dedup();
+        quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Alteration::*;
+    use super::Degree::*;
+    use super::*;
+    use test_case::case;
+
+    fn quality(
+        triad: Triad,
+        seventh: Option<Seventh>,
+        extension: Option<Extension>,
+        alterations: Vec<Alteration>,
+    ) -> ChordQuality {
+        ChordQuality {
+            triad,
+            seventh,
+            extension,
+            alterations,
+            ..Default::default()
+        }
+    }
+
+    #[case("", quality(Triad::Major, None, None, vec![]))]
+    #[case("m", quality(Triad::Minor, None, None, vec![]))]
+    #[case("min", quality(Triad::Minor, None, None, vec![]))]
+    #[case("-", quality(Triad::Minor, None, None, vec![]))]
+    #[case("dim", quality(Triad::Diminished, None, None, vec![]))]
+    #[case("°7", quality(Triad::Diminished, Some(Seventh::Diminished), None, vec![]))]
+    #[case("aug", quality(Triad::Augmented, None, None, vec![]))]
+    #[case("+", quality(Triad::Augmented, None, None, vec![]))]
+    #[case("7", quality(Triad::Major, Some(Seventh::Minor), None, vec![]))]
+    #[case("m7", quality(Triad::Minor, Some(Seventh::Minor), None, vec![]))]
+    #[case("mM7", quality(Triad::Minor, Some(Seventh::Major), None, vec![]))]
+    #[case("m(maj7)", quality(Triad::Minor, Some(Seventh::Major), None, vec![]))]
+    #[case("maj9", quality(Triad::Major, Some(Seventh::Major), Some(Extension::Ninth), vec![]))]
+    #[case("13", quality(Triad::Major, Some(Seventh::Minor), Some(Extension::Thirteenth), vec![]))]
+    #[case("7b9#11", quality(Triad::Major, Some(Seventh::Minor), None, vec![FlatNinth, SharpEleventh]))]
+    #[case("7(#9,b13)", quality(Triad::Major, Some(Seventh::Minor), None, vec![SharpNinth, FlatThirteenth]))]
+    #[case("+5", quality(Triad::Augmented, None, None, vec![]))]
+    #[case("-5", quality(Triad::Major, None, None, vec![FlatFifth]))]
+    #[case("7-5", quality(Triad::Major, Some(Seventh::Minor), None, vec![FlatFifth]))]
+    fn basics(input: &str, output: ChordQuality) {
+        assert_eq!(ChordQuality::parse(input), output);
+    }
+
+    #[case("maj7", "M7")]
+    #[case("maj7", "Δ7")]
+    #[case("maj7", "Δ")]
+    #[case("maj7", "ma7")]
+    #[case("m7b5", "ø7")]
+    #[case("m7b5", "ø")]
+    #[case("m7-5", "ø")]
+    #[case("7#5", "+7")]
+    #[case("7#5", "aug7")]
+    #[case("m7", "-7")]
+    #[case("sus", "sus4")]
+    #[case("69", "6add9")]
+    #[case("6/9", "69")]
+    #[case("7/6", "7add6")]
+    #[case("/9", "add9")]
+    #[case("maj", "")]
+    fn equivalent(first: &str, second: &str) {
+        assert_eq!(ChordQuality::parse(first), ChordQuality::parse(second));
+    }
+
+    #[test]
+    fn sus_add_omit() {
+        let value = ChordQuality::parse("7sus2add13no5");
+        assert_eq!(value.suspension(), Some(Suspension::Second));
+        assert_eq!(value.seventh(), Some(Seventh::Minor));
+        assert_eq!(value.added(), &[Thirteenth]);
+        assert_eq!(value.omitted(), &[Fifth]);
+    }
+
+    #[test]
+    fn power_chord() {
+        assert_eq!(ChordQuality::parse("5").omitted(), &[Third]);
+    }
+
+    #[test]
+    fn unknown_symbols_are_skipped() {
+        assert_eq!(ChordQuality::parse("m7xyz"), ChordQuality::parse("m7"));
+        assert_eq!(ChordQuality::parse("7)"), ChordQuality::parse("7"));
+    }
+
+    #[case("m7")]
+    #[case("7(#9,b13)")]
+    #[case("m(maj7)")]
+    #[case("6/9")]
+    #[case("7sus2add13no5")]
+    #[case("")]
+    fn strict_ok(input: &str) {
+        assert_eq!(
+            ChordQuality::parse_strict(input).unwrap(),
+            ChordQuality::parse(input)
+        );
+    }
+
+    #[case("xyz", 0..1)]
+    #[case("majj7", 3..4)]
+    #[case("(((", 0..1)]
+    #[case("7)", 1..2)]
+    #[case("m7(b9", 2..3)]
+    #[case("addx", 0..3)]
+    #[case("7omit7", 1..6)]
+    fn strict_err(input: &str, bytes: Range<usize>) {
+        let error = ChordQuality::parse_strict(input).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidQuality(input.into()));
+        assert_eq!(error.span().bytes(), bytes);
+    }
+}
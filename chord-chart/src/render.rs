@@ -0,0 +1,106 @@
+use crate::error::{Error, ErrorKind};
+use std::str::FromStr;
+
+/// How notes and bar lines are written out.
+/// Default matches the historical output: "| Hm | F# |".
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub note_naming: NoteNaming,
+    pub accidentals: Accidentals,
+    pub spacing: Spacing,
+}
+
+/// How note letters are written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NoteNaming {
+    /// B natural is "B".
+    English,
+    /// B natural is "H".
+    #[default]
+    German,
+    /// Fixed-do solfège: "Do", "Sol#", "Sib".
+    Solfege,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Accidentals {
+    /// "b", "#", "bb" and "##".
+    #[default]
+    Ascii,
+    /// "♭", "♯", "𝄫" and "𝄪".
+    Unicode,
+}
+
+/// Space around bar stripes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Spacing {
+    /// "| C D | E |"
+    #[default]
+    Normal,
+    /// "|C D|E|"
+    Compact,
+}
+
+impl FromStr for NoteNaming {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "english" | "b" => Ok(Self::English),
+            "german" | "h" => Ok(Self::German),
+            "solfege" | "solfège" => Ok(Self::Solfege),
+            _ => Err(Error::trimmed(ErrorKind::InvalidOption(s.trim().into()), s)),
+        }
+    }
+}
+
+impl FromStr for Accidentals {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ascii" => Ok(Self::Ascii),
+            "unicode" => Ok(Self::Unicode),
+            _ => Err(Error::trimmed(ErrorKind::InvalidOption(s.trim().into()), s)),
+        }
+    }
+}
+
+impl FromStr for Spacing {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "normal" => Ok(Self::Normal),
+            "compact" => Ok(Self::Compact),
+            _ => Err(Error::trimmed(ErrorKind::InvalidOption(s.trim().into()), s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("english", NoteNaming::English)]
+    #[case(" German ", NoteNaming::German)]
+    #[case("B", NoteNaming::English)]
+    #[case("h", NoteNaming::German)]
+    #[case("Solfège", NoteNaming::Solfege)]
+    fn note_naming(input: &str, output: NoteNaming) {
+        assert_eq!(NoteNaming::from_str(input).unwrap(), output);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Spacing::from_str("wide").unwrap_err().kind(),
+            &ErrorKind::InvalidOption("wide".into())
+        );
+        assert_eq!(
+            Accidentals::from_str("").unwrap_err().kind(),
+            &ErrorKind::InvalidOption("".into())
+        );
+    }
+}
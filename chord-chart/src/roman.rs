@@ -0,0 +1,263 @@
+use std::fmt;
+
+use crate::{
+    chart::Chart,
+    chord::Chord,
+    key::Key,
+    quality::{Alteration, ChordQuality, Degree, Extension, Seventh, Suspension, Triad},
+};
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+/// Chord as a degree of the key, with case showing the triad: "ii7", "V/V" or "bVII".
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RomanNumeral(String);
+
+impl fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub(crate) fn to_roman_numerals(chart: &Chart, key: &Key) -> Chart<RomanNumeral> {
+    chart.map(&|c| RomanNumeral(roman_numeral(c, key)))
+}
+
+fn roman_numeral(chord: &Chord, key: &Key) -> String {
+    if let Some(numeral) = secondary_dominant(chord, key) {
+        return numeral;
+    }
+
+    let (degree, offset) = key.mode_degree_of(chord.root());
+    accidental(offset) + &numeral(degree, chord.quality()) + &suffix(chord, key)
+}
+
+/// Dominant or leading-tone chord of a diatonic chord other than tonic: "V7/V", "vii°/vi".
+fn secondary_dominant(chord: &Chord, key: &Key) -> Option<String> {
+    let quality = chord.quality();
+    let semitones = key.tonic().get_semitones_diff(chord.root());
+
+    let (function, target) = match (quality.triad(), quality.seventh()) {
+        _ if quality.suspension().is_some() => return None,
+        (Triad::Major, None | Some(Seventh::Minor)) => (4, semitones - 7),
+        (Triad::Diminished, _) => (6, semitones + 1),
+        _ => return None,
+    };
+
+    let scale = key.mode().semitones();
+    let target = target.rem_euclid(12);
+    let target_degree = scale.iter().position(|s| s == &target)?;
+    let target_triad = diatonic_triad(&scale, target_degree);
+    if target == 0 || target_triad == Triad::Diminished || is_diatonic(chord, key) {
+        return None;
+    }
+
+    let target_numeral = numeral(target_degree as i32, &ChordQuality::of_triad(target_triad));
+    Some(numeral(function, quality) + &suffix(chord, key) + "/" + &target_numeral)
+}
+
+fn is_diatonic(chord: &Chord, key: &Key) -> bool {
+    let scale = key.mode().semitones();
+    let (degree, offset) = key.mode_degree_of(chord.root());
+    let degree = degree as usize;
+    let quality = chord.quality();
+
+    let seventh = match (scale[(degree + 6) % 7] - scale[degree]).rem_euclid(12) {
+        9 => Seventh::Diminished,
+        10 => Seventh::Minor,
+        _ => Seventh::Major,
+    };
+    offset == 0
+        && quality.triad() == diatonic_triad(&scale, degree)
+        && quality.seventh().map_or(true, |s| s == seventh)
+}
+
+fn diatonic_triad(scale: &[i32; 7], degree: usize) -> Triad {
+    let third = (scale[(degree + 2) % 7] - scale[degree]).rem_euclid(12);
+    let fifth = (scale[(degree + 4) % 7] - scale[degree]).rem_euclid(12);
+    match (third, fifth) {
+        (3, 6) => Triad::Diminished,
+        (3, _) => Triad::Minor,
+        (4, 8) => Triad::Augmented,
+        _ => Triad::Major,
+    }
+}
+
+fn accidental(offset: i32) -> String {
+    if offset < 0 {
+        "b".repeat(offset.unsigned_abs() as usize)
+    } else {
+        "#".repeat(offset as usize)
+    }
+}
+
+/// Numeral with case showing the triad, followed by the rest of the quality.
+fn numeral(degree: i32, quality: &ChordQuality) -> String {
+    let numeral = NUMERALS[degree as usize];
+    let mut value = match quality.triad() {
+        Triad::Major | Triad::Augmented => numeral.to_owned(),
+        Triad::Minor | Triad::Diminished => numeral.to_lowercase(),
+    };
+
+    value += match (quality.triad(), quality.seventh()) {
+        (Triad::Diminished, Some(Seventh::Minor)) => "ø",
+        (Triad::Diminished, _) => "°",
+        (Triad::Augmented, _) => "+",
+        _ => "",
+    };
+    if quality.seventh() == Some(Seventh::Major) {
+        value += "maj";
+    }
+    value
+}
+
+/// Extension or seventh of the chord, replaced with figured bass for inversions,
+/// then the rest of the quality and the bass note as "^5" if it's not a chord tone.
+fn suffix(chord: &Chord, key: &Key) -> String {
+    let quality = chord.quality();
+    let figure = match quality.extension() {
+        Some(extension) => Some(match extension {
+            Extension::Ninth => "9",
+            Extension::Eleventh => "11",
+            Extension::Thirteenth => "13",
+        }),
+        None if quality.seventh().is_some() => Some("7"),
+        None => None,
+    };
+
+    let inversion = chord.bass_note().map(|bass_note| {
+        let root = chord.root();
+        let interval = (
+            root.steps_to(bass_note),
+            root.get_semitones_diff(bass_note).rem_euclid(12),
+        );
+        let is_chord_tone = quality
+            .intervals()
+            .iter()
+            .any(|&(steps, semitones)| (steps % 7, semitones % 12) == interval);
+
+        match (interval.0, figure, is_chord_tone) {
+            (2, None, true) => Ok("6"),
+            (4, None, true) => Ok("64"),
+            (2, Some("7"), true) => Ok("65"),
+            (4, Some("7"), true) => Ok("43"),
+            (6, Some("7"), true) => Ok("42"),
+            _ => Err(bass_note),
+        }
+    });
+
+    let mut value = match inversion {
+        Some(Ok(figures)) => figures.to_owned(),
+        _ => figure.unwrap_or_default().to_owned(),
+    };
+    value += &details(quality);
+    if let Some(Err(bass_note)) = inversion {
+        let (degree, offset) = key.mode_degree_of(bass_note);
+        value += &format!("^{}{}", accidental(offset), degree + 1);
+    }
+    value
+}
+
+fn details(quality: &ChordQuality) -> String {
+    let mut value = String::new();
+    match quality.suspension() {
+        Some(Suspension::Second) => value += "sus2",
+        Some(Suspension::Fourth) => value += "sus4",
+        None => (),
+    }
+    for degree in quality.added() {
+        value += match degree {
+            Degree::Second => "add2",
+            Degree::Third => "add3",
+            Degree::Fourth => "add4",
+            Degree::Fifth => "add5",
+            Degree::Sixth => "add6",
+            Degree::Ninth => "add9",
+            Degree::Eleventh => "add11",
+            Degree::Thirteenth => "add13",
+        };
+    }
+    for alteration in quality.alterations() {
+        value += match alteration {
+            Alteration::FlatFifth => "b5",
+            Alteration::SharpFifth => "#5",
+            Alteration::FlatNinth => "b9",
+            Alteration::SharpNinth => "#9",
+            Alteration::SharpEleventh => "#11",
+            Alteration::FlatThirteenth => "b13",
+        };
+    }
+    for degree in quality.omitted() {
+        value += match degree {
+            Degree::Third => "no3",
+            Degree::Fifth => "no5",
+            _ => "",
+        };
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case("| Dm7 | G7 | Cmaj7 |", "C", "| ii7 | V7 | Imaj7 |")]
+    #[case("| C Am | F G |", "C", "| I vi | IV V |")]
+    #[case("| D7 | G |", "C", "| V7/V | V |")]
+    #[case("| E7 | Am |", "C", "| V7/vi | vi |")]
+    #[case("| C7 | F |", "C", "| V7/IV | IV |")]
+    #[case("| F#dim7 | G |", "C", "| vii°7/V | V |")]
+    #[case("| Bb | Ab | Fm |", "C", "| bVII | bVI | iv |")]
+    #[case("| Hm7b5 | E7 | Am |", "Am", "| iiø7 | V7 | i |")]
+    #[case("| Am | G | F | E |", "Am", "| i | VII | VI | V |")]
+    #[case("| Caug | Hdim |", "C", "| I+ | vii° |")]
+    #[case(
+        "| C/E | C/G | G7/H | G7/D | G7/F |",
+        "C",
+        "| I6 | I64 | V65 | V43 | V42 |"
+    )]
+    #[case("| F/G | C/Bb |", "C", "| IV^5 | I^b7 |")]
+    #[case("| D7/C | G |", "C", "| V42/V | V |")]
+    #[case("| A7/G | Dm |", "C", "| V42/ii | ii |")]
+    #[case("| D/E | G |", "C", "| V^3/V | V |")]
+    #[case("| D7/F# | G |", "C", "| V65/V | V |")]
+    #[case("| Gsus4 | C6 | G7b9 | C5 |", "C", "| Vsus4 | Iadd6 | V7b9 | Ino3 |")]
+    #[case("| D9 | Cmaj9 | Dm11 |", "C", "| V9/V | Imaj9 | ii11 |")]
+    #[case("| Em | D | C |", "E dorian", "| i | VII | bVI |")]
+    #[case("| F# | H | C#7 |", "Gb", "| I | IV | V7 |")]
+    #[case("| Ebm | Bbm |", "D#m", "| i | v |")]
+    fn to_roman_numerals(input: &str, key: &str, output: &str) {
+        let key = Key::from_str(key).unwrap();
+        assert_eq!(
+            Chart::from_str(input)
+                .unwrap()
+                .to_roman_numerals(&key)
+                .to_string(),
+            output
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let key = Key::from_str("C").unwrap();
+        let chart = Chart::from_str("[Verse]\n|: C Am :| x3\n| F G7 |").unwrap();
+        let analysis = chart.to_roman_numerals(&key);
+
+        assert_eq!(analysis.to_string(), "[Verse]\n|: I vi :| x3\n| IV V7 |");
+        assert_eq!(analysis.sections()[0].label(), Some("Verse"));
+        let bars: Vec<(usize, Vec<String>)> = analysis
+            .unroll()
+            .iter()
+            .map(|b| {
+                (
+                    b.line(),
+                    b.bar().chords().iter().map(|n| n.to_string()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(bars.len(), 4);
+        assert_eq!(bars[3], (2, vec!["IV".to_owned(), "V7".to_owned()]));
+    }
+}
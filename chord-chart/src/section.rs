@@ -0,0 +1,164 @@
+use std::fmt;
+
+use crate::{
+    bar_line::BarLine,
+    chord::Chord,
+    render::{RenderOptions, Spacing},
+    transpose::{Scale, Transpose},
+};
+
+/// Name of a song part, written as the chart author did: "[Chorus]" or "Chorus:".
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum Label {
+    Brackets(String),
+    Colon(String),
+}
+
+impl Label {
+    /// Reads a label written on its own line.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let trimmed = line.trim();
+        if trimmed.contains('|') {
+            return None;
+        }
+
+        let label = if let Some(name) = trimmed.strip_prefix('[') {
+            Self::Brackets(name.strip_suffix(']')?.trim().to_owned())
+        } else {
+            Self::Colon(trimmed.strip_suffix(':')?.trim().to_owned())
+        };
+        (!label.name().is_empty()).then_some(label)
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Self::Brackets(name) | Self::Colon(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Brackets(name) => write!(f, "[{name}]"),
+            Self::Colon(name) => write!(f, "{name}:"),
+        }
+    }
+}
+
+/// Part of a song: bar lines after a label, or before the first one.
+#[derive(Debug)]
+pub struct Section<C = Chord> {
+    label: Option<Label>,
+    /// Line of the chart the section starts on: its label or first bar line.
+    line: usize,
+    lines: Vec<BarLine<C>>,
+}
+
+impl<C> Section<C> {
+    pub(crate) fn new(label: Option<Label>, line: usize) -> Self {
+        Self {
+            label,
+            line,
+            lines: Vec::new(),
+        }
+    }
+
+    /// "Chorus" for both "[Chorus]" and "Chorus:".
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(Label::name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(BarLine::is_empty)
+    }
+
+    pub(crate) fn push(&mut self, line: BarLine<C>) {
+        self.lines.push(line);
+    }
+
+    pub(crate) fn chords(&self) -> impl Iterator<Item = &C> {
+        self.lines.iter().flat_map(BarLine::chords)
+    }
+
+    /// Bar lines with lines of the chart they are written on.
+    pub(crate) fn bar_lines(&self) -> impl Iterator<Item = (usize, &BarLine<C>)> {
+        let first = self.line + usize::from(self.label.is_some());
+        self.lines
+            .iter()
+            .enumerate()
+            .map(move |(i, l)| (first + i, l))
+    }
+
+    /// Same section with each chord replaced by `f` of it.
+    pub(crate) fn map<D>(&self, f: &impl Fn(&C) -> D) -> Section<D> {
+        Section {
+            label: self.label.clone(),
+            line: self.line,
+            lines: self.lines.iter().map(|l| l.map(f)).collect(),
+        }
+    }
+
+    /// Label, if any, and every bar line that isn't empty.
+    pub(crate) fn format_with(
+        &self,
+        spacing: Spacing,
+        format_chord: &impl Fn(&C) -> String,
+    ) -> Vec<String> {
+        let label = self.label.iter().map(Label::to_string);
+        label
+            .chain(self.format_lines(spacing, format_chord))
+            .collect()
+    }
+
+    fn format_lines(&self, spacing: Spacing, format_chord: &impl Fn(&C) -> String) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.format_with(spacing, format_chord))
+            .collect()
+    }
+}
+
+impl Section {
+    /// Bar lines of the section, without the label.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.format_lines(options.spacing, &|c| c.render(options))
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&RenderOptions::default()))
+    }
+}
+
+impl Transpose for Section {
+    fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self {
+        self.map(&|c| c.transpose(semitone_incr, scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("[Chorus]", Label::Brackets("Chorus".into()))]
+    #[case("  [ Verse 1 ] ", Label::Brackets("Verse 1".into()))]
+    #[case("Intro:", Label::Colon("Intro".into()))]
+    #[case("Pre-chorus :", Label::Colon("Pre-chorus".into()))]
+    fn label(input: &str, output: Label) {
+        assert_eq!(Label::parse(input), Some(output));
+    }
+
+    #[case("[]" ; "empty brackets")]
+    #[case(":" ; "only colon")]
+    #[case("[Chorus")]
+    #[case("| C |:")]
+    #[case("A/E E")]
+    fn not_label(input: &str) {
+        assert_eq!(Label::parse(input), None);
+    }
+}
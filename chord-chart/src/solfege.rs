@@ -0,0 +1,56 @@
+use crate::{
+    chart::Chart,
+    key::{Key, Mode},
+    note::Note,
+    render::Spacing,
+};
+
+/// Movable-do syllables by semitones above do, for raised and lowered degrees.
+const RAISED: [&str; 12] = [
+    "Do", "Di", "Re", "Ri", "Mi", "Fa", "Fi", "Sol", "Si", "La", "Li", "Ti",
+];
+const LOWERED: [&str; 12] = [
+    "Do", "Ra", "Re", "Me", "Mi", "Fa", "Se", "Sol", "Le", "La", "Te", "Ti",
+];
+
+pub(crate) fn to_movable_do(chart: &Chart, key: &Key, spacing: Spacing) -> String {
+    let major = Key::new(key.relative_major(), Mode::Major);
+    chart.format_with(spacing, &|c| c.format_with(&|n| format_syllable(n, &major)))
+}
+
+fn format_syllable(note: &Note, key: &Key) -> String {
+    let (_, offset) = key.degree_of(note);
+    let semitones = (note.semitone() - key.tonic().semitone()).rem_euclid(12) as usize;
+    if offset < 0 {
+        LOWERED[semitones].into()
+    } else {
+        RAISED[semitones].into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case("| C F | Am G/H |", "C", "| Do Fa | Lam Sol/Ti |")]
+    #[case("| Am | Dm | E7 | Am |", "Am", "| Lam | Rem | Mi7 | Lam |")]
+    #[case("| D | F#m | Hm7 |", "D", "| Do | Mim | Lam7 |")]
+    #[case("| E7/G# | Bb | Ab |", "C", "| Mi7/Si | Te | Le |")]
+    #[case("| C#dim | Eb |", "C", "| Didim | Me |")]
+    #[case("| E#m | Cb |", "C", "| Fam | Ti |")]
+    #[case("| Eb | Ab | Bb |", "Eb", "| Do | Fa | Sol |")]
+    fn to_movable_do(input: &str, key: &str, output: &str) {
+        let key = Key::from_str(key).unwrap();
+        let chart = Chart::from_str(input).unwrap();
+        assert_eq!(chart.to_movable_do(&key, Spacing::Normal), output);
+    }
+
+    #[test]
+    fn compact() {
+        let key = Key::from_str("G").unwrap();
+        let chart = Chart::from_str("| G C | D7 |").unwrap();
+        assert_eq!(chart.to_movable_do(&key, Spacing::Compact), "|Do Fa|Sol7|");
+    }
+}
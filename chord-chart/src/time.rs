@@ -0,0 +1,107 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    chart::Chart,
+    error::{Error, ErrorKind},
+    section::Section,
+};
+
+/// Meter: beats in a bar over the note value of a beat, "4/4", "3/4" or "6/8".
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TimeSignature {
+    beats: u32,
+    unit: u32,
+}
+
+impl TimeSignature {
+    pub fn beats(self) -> u32 {
+        self.beats
+    }
+
+    /// Note value of a beat: 4 for quarter notes, 8 for eighths.
+    pub fn unit(self) -> u32 {
+        self.unit
+    }
+
+    /// Digits around a slash, as time signatures are written, even if they
+    /// don't make one: "3/5".
+    pub(crate) fn looks_like(s: &str) -> bool {
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        matches!(s.split_once('/'), Some((beats, unit)) if digits(beats) && digits(unit))
+    }
+}
+
+impl FromStr for TimeSignature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || Error::new(ErrorKind::InvalidTimeSignature(s.into()), s, 0..s.len());
+        let (beats, unit) = s
+            .split_once('/')
+            .filter(|_| Self::looks_like(s))
+            .ok_or_else(error)?;
+        match (beats.parse(), unit.parse()) {
+            (Ok(beats), Ok(unit)) if beats > 0 && u32::is_power_of_two(unit) && unit <= 64 => {
+                Ok(Self { beats, unit })
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.beats, self.unit)
+    }
+}
+
+pub(crate) fn time_signature(chart: &Chart) -> Option<TimeSignature> {
+    let mut time = None;
+    for (_, line) in chart.sections().iter().flat_map(Section::bar_lines) {
+        time = line.time_signature().or(time);
+        if let Some(bar) = line.bars().first() {
+            return bar.time_signature().or(time);
+        }
+    }
+    time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("4/4", 4, 4)]
+    #[case("6/8", 6, 8)]
+    #[case("7/8", 7, 8)]
+    #[case("12/16", 12, 16)]
+    fn parse(input: &str, beats: u32, unit: u32) {
+        let time = TimeSignature::from_str(input).unwrap();
+        assert_eq!((time.beats(), time.unit()), (beats, unit));
+        assert_eq!(time.to_string(), input);
+    }
+
+    #[case("3/5")]
+    #[case("0/4")]
+    #[case("4/128")]
+    #[case("4/")]
+    #[case("C/E")]
+    #[case("4 / 4")]
+    fn parse_err(input: &str) {
+        let err = TimeSignature::from_str(input).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidTimeSignature(input.into()));
+    }
+
+    #[case("4/4\n| C | D |", Some("4/4"))]
+    #[case("\n[Verse]\n3/4 | C | D |", Some("3/4"))]
+    #[case("4/4\n| 6/8 C | D |", Some("6/8"))]
+    #[case("| C | 3/4 D |", None)]
+    #[case("| C |\n6/8", None)]
+    fn chart(input: &str, time: Option<&str>) {
+        let chart = Chart::from_str(input).unwrap();
+        assert_eq!(
+            chart.time_signature().map(|t| t.to_string()).as_deref(),
+            time
+        );
+    }
+}
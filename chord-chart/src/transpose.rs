@@ -1,8 +1,55 @@
+use crate::note::Note;
+
 pub trait Transpose {
     fn transpose(&self, semitone_incr: &i32, scale: &Scale) -> Self;
 }
 
-pub enum Scale {
-    Major,
-    Minor,
+/// Spells transposed notes following key signature of the major scale of `tonic`.
+pub struct Scale {
+    tonic: Note,
+}
+
+impl Scale {
+    pub(crate) fn major(tonic: &Note) -> Self {
+        // There are no keys with more than seven sharps or flats: G# is written as Ab
+        let tonic = match tonic.fifths() {
+            fifths if fifths > 7 => tonic.add_interval(1, 0),
+            fifths if fifths < -7 => tonic.add_interval(-1, 0),
+            _ => tonic.clone(),
+        };
+        Self { tonic }
+    }
+
+    /// Diatonic notes come from key signature, chromatic ones are spelled
+    /// as the most common altered degrees: b2, b3, #4, b6 and b7.
+    pub(crate) fn spell(&self, semitone: i32) -> Note {
+        let (steps, semitones) = match (semitone - self.tonic.semitone()).rem_euclid(12) {
+            1 => (1, 1),
+            3 => (2, 3),
+            6 => (3, 6),
+            8 => (5, 8),
+            10 => (6, 10),
+            degree => (Self::MAJOR_STEPS[degree as usize], degree),
+        };
+        self.tonic
+            .try_add_interval(steps, semitones)
+            .unwrap_or_else(|| Note::from_semitone(semitone, self.tonic.fifths() >= 0))
+    }
+
+    /// Like `spell`, but chromatic degrees are raised, which suits leading-tone chords:
+    /// G#° in C leads to Am, while Ab° would not.
+    pub(crate) fn spell_raised(&self, semitone: i32) -> Note {
+        let (steps, semitones) = match (semitone - self.tonic.semitone()).rem_euclid(12) {
+            1 => (0, 1),
+            3 => (1, 3),
+            8 => (4, 8),
+            _ => return self.spell(semitone),
+        };
+        self.tonic
+            .try_add_interval(steps, semitones)
+            .unwrap_or_else(|| self.spell(semitone))
+    }
+
+    /// Letter steps above tonic for each degree of major scale by semitone.
+    const MAJOR_STEPS: [i32; 12] = [0, 0, 1, 1, 2, 3, 3, 4, 4, 5, 5, 6];
 }
@@ -0,0 +1,261 @@
+use crate::{
+    bar::Bar,
+    chart::Chart,
+    chord::Chord,
+    navigation::{Marker, Until},
+    time::TimeSignature,
+};
+
+/// Bar as it is played, with where it is written in the chart.
+#[derive(Debug)]
+pub struct PlayedBar<'a, C = Chord> {
+    bar: &'a Bar<C>,
+    line: usize,
+    index: usize,
+    time: Option<TimeSignature>,
+}
+
+// Derived ones would need chords to be `Copy` too
+impl<C> Clone for PlayedBar<'_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for PlayedBar<'_, C> {}
+
+impl<'a, C> PlayedBar<'a, C> {
+    pub fn bar(&self) -> &'a Bar<C> {
+        self.bar
+    }
+
+    /// Line of the chart, counting from zero as `Span::line` does.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Bar in that line, counting from zero.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Time signature the bar is in: its own or the last one written before it.
+    pub fn time_signature(&self) -> Option<TimeSignature> {
+        self.time
+    }
+}
+
+/// Bar with the repeat signs around it.
+struct Slot<'a, C> {
+    played: PlayedBar<'a, C>,
+    starts_repeat: bool,
+    /// Times the repeat that ends after the bar is played.
+    ends_repeat: Option<u32>,
+}
+
+pub(crate) fn unroll<C>(chart: &Chart<C>) -> Vec<PlayedBar<'_, C>> {
+    let slots = slots(chart);
+    let mut played = Vec::new();
+    let mut i = 0;
+    // "|:" to go back to and pass through that repeat, counting from one
+    let (mut start, mut pass) = (0, 1);
+    let mut in_ending = false;
+    let mut jumped = false;
+    // Where the D.C. or D.S. plays to while going through the chart again
+    let mut until = None;
+    let mut jumped_back = false;
+    while let Some(slot) = slots.get(i) {
+        if slot.starts_repeat && !jumped && until.is_none() {
+            (start, pass) = (i, 1);
+            in_ending = false;
+        }
+        jumped = false;
+        if let Some(volta) = slot.played.bar.volta() {
+            let end = skip_ending(&slots, i);
+            let skip = match until {
+                Some(_) => slots[end - 1].ends_repeat.is_some(),
+                None => !volta.passes().contains(&pass),
+            };
+            if skip {
+                i = end;
+                continue;
+            }
+            in_ending = true;
+        }
+
+        played.push(slot.played);
+        let markers = slot.played.bar.markers();
+        if until == Some(Until::Fine) && markers.contains(&Marker::Fine) {
+            break;
+        }
+        if until == Some(Until::Coda) && markers.contains(&Marker::ToCoda) {
+            // Coda in the same bar would play it again
+            if let Some(coda) = find(&slots[i + 1..], Marker::Coda) {
+                i += coda + 1;
+                until = None;
+                continue;
+            }
+        }
+        match slot.ends_repeat {
+            // Ending that ends with ":|" goes back while there's one for the next pass
+            Some(times)
+                if until.is_none()
+                    && (pass < times || in_ending && has_ending(&slots, start, pass + 1)) =>
+            {
+                (i, pass) = (start, pass + 1);
+                in_ending = false;
+                jumped = true;
+            }
+            _ => match markers.iter().find_map(|m| jump(&slots, *m)) {
+                Some((target, to)) if !jumped_back => {
+                    i = target;
+                    until = Some(to);
+                    jumped_back = true;
+                }
+                _ => i += 1,
+            },
+        }
+    }
+    played
+}
+
+/// Slot that D.C. or D.S. goes back to, with where it plays to.
+fn jump<C>(slots: &[Slot<C>], marker: Marker) -> Option<(usize, Until)> {
+    match marker {
+        Marker::DaCapo(until) => Some((0, until)),
+        Marker::DalSegno(until) => Some((find(slots, Marker::Segno)?, until)),
+        _ => None,
+    }
+}
+
+fn find<C>(slots: &[Slot<C>], marker: Marker) -> Option<usize> {
+    slots
+        .iter()
+        .position(|slot| slot.played.bar.markers().contains(&marker))
+}
+
+fn slots<C>(chart: &Chart<C>) -> Vec<Slot<'_, C>> {
+    let mut slots = Vec::new();
+    let mut time = None;
+    for section in chart.sections() {
+        for (line, bar_line) in section.bar_lines() {
+            time = bar_line.time_signature().or(time);
+            for (index, bar) in bar_line.bars().iter().enumerate() {
+                time = bar.time_signature().or(time);
+                slots.push(Slot {
+                    played: PlayedBar {
+                        bar,
+                        line,
+                        index,
+                        time,
+                    },
+                    starts_repeat: bar_line.starts_repeat(index),
+                    ends_repeat: bar_line.ends_repeat(index),
+                });
+            }
+        }
+    }
+    slots
+}
+
+/// Whether the repeat that starts at `start` has an ending for `pass`.
+fn has_ending<C>(slots: &[Slot<C>], start: usize, pass: u32) -> bool {
+    let mut i = start;
+    let mut in_endings = false;
+    while let Some(slot) = slots.get(i) {
+        match slot.played.bar.volta() {
+            Some(volta) if volta.passes().contains(&pass) => return true,
+            Some(_) => {
+                in_endings = true;
+                i = skip_ending(slots, i);
+            }
+            None if in_endings => return false,
+            None => i += 1,
+        }
+    }
+    false
+}
+
+/// Slot after the ending that starts at `i`: past its ":|" or at the next
+/// ending or repeat.
+fn skip_ending<C>(slots: &[Slot<C>], mut i: usize) -> usize {
+    loop {
+        let ends_repeat = slots[i].ends_repeat.is_some();
+        i += 1;
+        match slots.get(i) {
+            Some(slot) if !ends_repeat => {
+                if slot.starts_repeat || slot.played.bar.volta().is_some() {
+                    return i;
+                }
+            }
+            _ => return i,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::case;
+
+    #[case("| C | D |", "C D")]
+    #[case("|: C | D :|", "C D C D")]
+    #[case("|: C :| x3", "C C C")]
+    #[case("| C |: D :|: E :| F |", "C D D E E F")]
+    #[case("|: C |\n| D :|\n| E |", "C D C D E")]
+    #[case("| C | D :| E |", "C D C D E")]
+    #[case("|: C |1. D :|2. E |", "C D C E")]
+    #[case("|: C |1. D | E :|2. F | G |", "C D E C F G")]
+    #[case("|: C |1. D :|2. E :|3. F |", "C D C E C F")]
+    #[case("|: C |1,2. D :|3. E |", "C D C D C E")]
+    #[case("|: C |1. D :|\n|2. E |\n|: F :|", "C D C E F F")]
+    #[case("| C | D D.C. |", "C D C D")]
+    #[case("| C | Segno D | E D.S. |", "C D E D E")]
+    #[case("| C Fine | D D.C. al Fine |", "C D C")]
+    #[case("| C To Coda | D D.C. al Coda | Coda E |", "C D C E")]
+    #[case(
+        "| C | Segno D To Coda | E D.S. al Coda |\n| Coda F | G |",
+        "C D E D F G"
+    )]
+    #[case("|: C :| D D.C. |", "C C D C D")]
+    #[case("|: C |1. D :|2. E D.C. |", "C D C E C E")]
+    #[case("| C D.C. | D D.C. |", "C C D")]
+    #[case("| C | D D.S. |", "C D" ; "no segno")]
+    #[case("| C To Coda | D D.C. al Coda |", "C D C D" ; "no coda")]
+    #[case("| C Coda To Coda | D D.C. al Coda | Coda E |", "C D C E" ; "coda in the same bar")]
+    #[case("|: C |1,1000000. D :| E |", "C D C E" ; "ending past the last pass")]
+    #[case("|: C |1,3. D :|2. E :| F |", "C D C E C D F")]
+    fn unroll(input: &str, output: &str) {
+        // Unmatched ":|" is an error, but stays in the chart
+        let (chart, _) = Chart::from_str_recovering(input, &Default::default());
+        let bars: Vec<String> = chart
+            .unroll()
+            .iter()
+            .map(|b| b.bar().chords()[0].to_string())
+            .collect();
+        assert_eq!(bars.join(" "), output);
+    }
+
+    #[test]
+    fn positions() {
+        let chart = Chart::from_str("\n\n[Verse]\n|: C | D :|\n| E |").unwrap();
+        let positions: Vec<(usize, usize)> = chart
+            .unroll()
+            .iter()
+            .map(|b| (b.line(), b.index()))
+            .collect();
+        assert_eq!(positions, [(3, 0), (3, 1), (3, 0), (3, 1), (4, 0)]);
+    }
+
+    #[test]
+    fn time_signatures() {
+        let chart = Chart::from_str("4/4\n|: C | 3/4 D :|\n6/8 | E | F |\n| 7/8 G |").unwrap();
+        let times: Vec<String> = chart
+            .unroll()
+            .iter()
+            .map(|b| b.time_signature().unwrap().to_string())
+            .collect();
+        assert_eq!(times, ["4/4", "3/4", "4/4", "3/4", "6/8", "6/8", "7/8"]);
+    }
+}
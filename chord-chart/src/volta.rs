@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// Ending of a repeat, played on the passes it is numbered with: "1." or "1,2.".
+/// Covers bars from the one it is written on to the next ":|" or ending.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Volta(Vec<u32>);
+
+impl Volta {
+    /// Passes through the repeat, counting from one.
+    pub fn passes(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// Reads "1." or "1,2." at the start of a bar, returning the rest of it.
+    pub(crate) fn parse_prefix(s: &str) -> Option<(Self, &str)> {
+        let (numbers, rest) = s.split_once('.')?;
+        let passes = numbers
+            .split(',')
+            .map(|n| match n.parse() {
+                Ok(pass) if pass > 0 && n.bytes().all(|b| b.is_ascii_digit()) => Some(pass),
+                _ => None,
+            })
+            .collect::<Option<Vec<u32>>>()?;
+        Some((Self(passes), rest))
+    }
+}
+
+impl fmt::Display for Volta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let passes: Vec<String> = self.0.iter().map(u32::to_string).collect();
+        write!(f, "{}.", passes.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::case;
+
+    #[case("1. C", &[1], " C")]
+    #[case("2.Am", &[2], "Am")]
+    #[case("1,2,3.", &[1, 2, 3], "")]
+    fn parse_prefix(input: &str, passes: &[u32], rest: &str) {
+        let (volta, r) = Volta::parse_prefix(input).unwrap();
+        assert_eq!(volta.passes(), passes);
+        assert_eq!(r, rest);
+        assert_eq!(volta.to_string() + r, input);
+    }
+
+    #[case("C")]
+    #[case("0.")]
+    #[case("1 ." ; "space before dot")]
+    #[case("1,." ; "empty pass")]
+    #[case("+1." ; "sign")]
+    #[case("C7.")]
+    fn not_volta(input: &str) {
+        assert_eq!(Volta::parse_prefix(input), None);
+    }
+}
@@ -1,6 +1,22 @@
+// `transpose_integration` keeps using the deprecated `Accidental::scale`
+#![allow(deprecated)]
+
 use std::str::FromStr;
 
-use chord_chart::{Chart, Error, Note, Transpose};
+use chord_chart::{Chart, Error, Key, Note, TimeSignature, Transpose};
+
+#[test]
+fn error_integration() -> Result<(), Box<dyn std::error::Error>> {
+    let error = Chart::from_str("| C |\n| Am | W7 |").unwrap_err();
+    assert_eq!(error.kind().code(), "E002");
+    assert_eq!(error.to_string(), "invalid natural: W at line 2, column 8");
+
+    let boxed: Box<dyn std::error::Error> = Box::new(error);
+    assert_eq!(boxed.to_string(), "invalid natural: W at line 2, column 8");
+
+    Chart::from_str("| C |")?;
+    Ok(())
+}
 
 #[test]
 fn transpose_integration() -> Result<(), Error> {
@@ -21,3 +37,115 @@ fn transpose_integration() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn transpose_integration_key_signature() -> Result<(), Error> {
+    let current_key = Note::from_str("C")?;
+    let current_chords = Chart::from_str("| C | F | G7 | Am |")?;
+    let new_key = Note::from_str("F")?;
+    let expected_new_chords = "| F | Bb | C7 | Dm |";
+
+    assert_eq!(
+        current_chords
+            .transpose(&current_key.get_semitones_diff(&new_key), &new_key.scale())
+            .to_string(),
+        expected_new_chords
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transpose_to_integration() -> Result<(), Error> {
+    let current_key = Key::from_str("Em")?;
+    let current_chords = Chart::from_str("| Em | C D | H7 |")?;
+    let new_key = Key::from_str("Fm")?;
+    let expected_new_chords = "| Fm | Db Eb | C7 |";
+
+    assert_eq!(
+        current_chords
+            .transpose_to(&current_key, &new_key)
+            .to_string(),
+        expected_new_chords
+    );
+
+    Ok(())
+}
+
+#[test]
+fn detect_key_integration() -> Result<(), Error> {
+    let chart = Chart::from_str("| Em | Am | H7 | Em |")?;
+    let new_key = Key::from_str("Gm")?;
+    let expected_new_chords = "| Gm | Cm | D7 | Gm |";
+
+    let candidates = chart.detect_key();
+    assert_eq!(candidates[0].key(), &Key::from_str("Em")?);
+    assert_eq!(
+        chart
+            .transpose_to(candidates[0].key(), &new_key)
+            .to_string(),
+        expected_new_chords
+    );
+
+    Ok(())
+}
+
+#[test]
+fn nashville_integration() -> Result<(), Error> {
+    let chart = Chart::from_str("| G D/F# | Em C |\n| Am7 | D7sus4 |")?;
+    let numbers = chart.to_nashville(&Key::from_str("G")?);
+    assert_eq!(numbers, "| 1 5/7 | 6m 4 |\n| 2m7 | 5(7sus4) |");
+
+    let new_chart = Chart::from_nashville(&numbers, &Key::from_str("Bb")?)?;
+    assert_eq!(
+        new_chart.to_string(),
+        "| Bb F/A | Gm Eb |\n| Cm7 | F7sus4 |"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unroll_integration() -> Result<(), Error> {
+    let chart = Chart::from_str("[Verse]\n|: C | G |\n|1. Am | F :|\n|2. Dm | G |")?;
+    let played: Vec<(usize, String)> = chart
+        .unroll()
+        .iter()
+        .map(|b| (b.line(), b.bar().to_string()))
+        .collect();
+
+    assert_eq!(
+        played,
+        [
+            (1, "C".into()),
+            (1, "G".into()),
+            (2, "1. Am".into()),
+            (2, "F".into()),
+            (1, "C".into()),
+            (1, "G".into()),
+            (3, "2. Dm".into()),
+            (3, "G".into()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn time_signature_integration() -> Result<(), Error> {
+    let chart = Chart::from_str("6/8\n| C | G |\n| 3/4 Am | F |")?;
+    assert_eq!(
+        chart.time_signature(),
+        Some(TimeSignature::from_str("6/8")?)
+    );
+
+    let beats: Vec<u32> = chart
+        .unroll()
+        .iter()
+        .filter_map(|b| b.time_signature())
+        .map(TimeSignature::beats)
+        .collect();
+    assert_eq!(beats, [6, 6, 3, 3]);
+
+    Ok(())
+}